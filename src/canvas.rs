@@ -1,66 +1,109 @@
+mod compare;
+mod contact_sheet;
+mod deflate;
+mod draw;
+mod exr;
+mod filter;
+mod geometry;
+mod hdr;
+mod iter;
+mod pixel;
+mod quantize;
+mod resample;
+mod statistics;
+mod terminal;
+mod text;
+mod tga;
+mod tone_mapping;
+
 use crate::color::Color;
 
-pub struct Canvas {
+pub use self::contact_sheet::ContactSheet;
+pub use self::exr::{ExrCompression, ExrPixelType, ExrWriter};
+pub use self::filter::{EdgeMode, Kernel};
+pub use self::hdr::HdrError;
+pub use self::iter::Tile;
+pub use self::pixel::{Pixel, Rgb32, Rgba};
+pub use self::quantize::Quantization;
+pub use self::resample::ResizeFilter;
+pub use self::statistics::Histogram;
+pub use self::tga::TgaError;
+pub use self::tone_mapping::{Exposure, ToneMapOperator};
+
+/// A grid of pixels stored row by row. Most operations work on the default
+/// `Color` pixels, other pixel types are converted with [`Canvas::convert`].
+/// The TGA, Radiance HDR and OpenEXR exports accept any [`Pixel`] directly.
+pub struct Canvas<P = Color> {
     width: usize,
     height: usize,
-    bitmap: Vec<Color>,
+    bitmap: Vec<P>,
 }
 
 impl Canvas {
     pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas::filled(width, height, Color::black())
+    }
+
+    // applies f to every pixel of a copy, shared by the color adjustments
+    pub(crate) fn map_colors<F: Fn(Color) -> Color>(&self, f: F) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            bitmap: self.bitmap.iter().map(|color| f(*color)).collect(),
+        }
+    }
+}
+
+impl<P: Copy> Canvas<P> {
+    pub fn filled(width: usize, height: usize, pixel: P) -> Canvas<P> {
         Canvas {
             width,
             height,
-            bitmap: vec![Color::black(); width * height],
+            bitmap: vec![pixel; width * height],
         }
     }
 
-    pub fn write_pixel(&mut self, x: usize, y: usize, color: Color) {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn write_pixel(&mut self, x: usize, y: usize, pixel: P) {
         assert!(x < self.width); // TODO: real error handling
         assert!(y < self.height);
 
-        self.bitmap[(y * self.width) + x] = color;
+        self.bitmap[(y * self.width) + x] = pixel;
     }
 
-    pub fn pixel_at(&self, x: usize, y: usize) -> Color {
+    pub fn pixel_at(&self, x: usize, y: usize) -> P {
         assert!(x < self.width); // TODO: real error handling
         assert!(y < self.height);
 
         self.bitmap[(y * self.width) + x]
     }
+}
 
-    pub fn to_tga(&self) -> Vec<u8> {
-        let mut tga: [u8; 18] = [0; 18];
-
-        // tga header
-        // see: http://www.gamers.org/dEngine/quake3/TGA.txt
-        tga[2] = 2; // uncompressed RGB data
-        tga[12] = (255 & self.width) as u8;
-        tga[13] = (255 & (self.width >> 8)) as u8;
-        tga[14] = (255 & self.height) as u8;
-        tga[15] = (255 & (self.height >> 8)) as u8;
-        tga[16] = 24; // 24 bits per pixel
-        tga[17] = 0b0010_0000; // screen origin upper left-hand corner
-
-        let mut image: Vec<u8> = tga.to_vec();
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                let color = self.pixel_at(x, y);
-
-                image.push(Color::convert_component(color.blue));
-                image.push(Color::convert_component(color.green));
-                image.push(Color::convert_component(color.red));
-            }
+impl<P: Pixel> Canvas<P> {
+    /// Converts every pixel to another pixel type, going through `Color`.
+    pub fn convert<Q: Pixel>(&self) -> Canvas<Q> {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            bitmap: self
+                .bitmap
+                .iter()
+                .map(|pixel| Q::from_color(pixel.to_color()))
+                .collect(),
         }
-
-        image
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Canvas;
+    use super::{Canvas, ExrPixelType, ExrWriter, Rgb32, Rgba};
     use crate::color::Color;
 
     #[test]
@@ -87,4 +130,44 @@ mod test {
         c.write_pixel(5, 5, red);
         assert_eq!(c.pixel_at(5, 5), Color::new(1.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn canvases_of_other_pixel_types() {
+        let mut depth = Canvas::filled(4, 3, f32::INFINITY);
+        depth.write_pixel(1, 2, 2.5);
+
+        assert_eq!(depth.pixel_at(1, 2), 2.5);
+        assert_eq!(depth.pixel_at(0, 0), f32::INFINITY);
+        assert_eq!(depth.crop(1, 1, 2, 2).pixel_at(0, 1), 2.5);
+    }
+
+    #[test]
+    fn converting_between_pixel_types() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(1, 0, Color::new(0.5, 0.25, 1.0));
+
+        let compact: Canvas<Rgb32> = c.convert();
+        assert_eq!(compact.pixel_at(1, 0), Rgb32::new(0.5, 0.25, 1.0));
+        assert_eq!(compact.convert::<Color>().pixel_at(1, 0), c.pixel_at(1, 0));
+
+        let alpha: Canvas<Rgba> = compact.convert();
+        assert_eq!(alpha.pixel_at(0, 0), Rgba::new(0.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn exporting_other_pixel_types() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(1, 0, Color::new(0.5, 0.25, 1.0));
+        c.write_pixel(2, 1, Color::new(4.0, 0.0, 0.125));
+
+        let compact: Canvas<Rgb32> = c.convert();
+        assert_eq!(compact.to_tga(), c.to_tga());
+        assert_eq!(compact.to_hdr(), c.to_hdr());
+        assert_eq!(compact.to_exr(), c.to_exr());
+
+        let depth = Canvas::filled(3, 2, 0.25_f32);
+        let written = ExrWriter::new(&depth).pixel_type(ExrPixelType::Half).to_bytes();
+        let converted: Canvas = depth.convert();
+        assert_eq!(written, ExrWriter::new(&converted).pixel_type(ExrPixelType::Half).to_bytes());
+    }
 }
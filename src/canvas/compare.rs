@@ -0,0 +1,349 @@
+use std::f64::consts::PI;
+
+use crate::canvas::filter::EdgeMode;
+use crate::canvas::Canvas;
+use crate::color::{Color, D65};
+
+// FLIP viewing conditions: a 0.7 m distance to a 0.3 m wide 3840 pixel monitor
+const PIXELS_PER_DEGREE: f64 = 67.0;
+
+impl Canvas {
+    /// Per pixel absolute difference of every channel.
+    pub fn difference(&self, other: &Canvas) -> Canvas {
+        self.assert_same_size(other);
+
+        self.combine(other, |a, b| {
+            Color::new(
+                (a.red - b.red).abs(),
+                (a.green - b.green).abs(),
+                (a.blue - b.blue).abs(),
+            )
+        })
+    }
+
+    /// Largest absolute difference of any channel. Infinite as soon as any
+    /// difference is NaN or infinite, so broken pixels never compare equal.
+    pub fn max_error(&self, other: &Canvas) -> f64 {
+        self.difference(other)
+            .bitmap
+            .iter()
+            .map(|c| {
+                if c.is_finite() {
+                    c.max_component()
+                } else {
+                    f64::INFINITY
+                }
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Absolute difference averaged over all channels.
+    pub fn mean_error(&self, other: &Canvas) -> f64 {
+        let total: f64 = self
+            .difference(other)
+            .bitmap
+            .iter()
+            .map(|c| c.red + c.green + c.blue)
+            .sum();
+        total / (3 * self.bitmap.len()).max(1) as f64
+    }
+
+    /// Peak signal to noise ratio in decibels for values in `[0, 1]`, infinite
+    /// for identical canvases.
+    pub fn psnr(&self, other: &Canvas) -> f64 {
+        let squared: f64 = self
+            .difference(other)
+            .bitmap
+            .iter()
+            .map(|c| c.red * c.red + c.green * c.green + c.blue * c.blue)
+            .sum();
+        let mse = squared / (3 * self.bitmap.len()).max(1) as f64;
+        -10.0 * mse.log10()
+    }
+
+    /// Mean structural similarity of the luminance, using the usual Gaussian
+    /// window with a standard deviation of 1.5 pixels.
+    pub fn ssim(&self, other: &Canvas) -> f64 {
+        self.assert_same_size(other);
+        let (c1, c2) = (0.01f64.powi(2), 0.03f64.powi(2));
+
+        let x = self.map_scalar(|c| c.luminance());
+        let y = other.map_scalar(|c| c.luminance());
+        let blur = |c: &Canvas| c.gaussian_blur(1.5, EdgeMode::Clamp);
+        let mean_x = blur(&x);
+        let mean_y = blur(&y);
+        let mean_xx = blur(&x.combine(&x, |a, b| a * b));
+        let mean_yy = blur(&y.combine(&y, |a, b| a * b));
+        let mean_xy = blur(&x.combine(&y, |a, b| a * b));
+
+        let mut total = 0.0;
+        for i in 0..self.bitmap.len() {
+            let (mx, my) = (mean_x.bitmap[i].red, mean_y.bitmap[i].red);
+            let variance_x = mean_xx.bitmap[i].red - mx * mx;
+            let variance_y = mean_yy.bitmap[i].red - my * my;
+            let covariance = mean_xy.bitmap[i].red - mx * my;
+
+            total += ((2.0 * mx * my + c1) * (2.0 * covariance + c2))
+                / ((mx * mx + my * my + c1) * (variance_x + variance_y + c2));
+        }
+        total / self.bitmap.len().max(1) as f64
+    }
+
+    /// Mean of [`flip_map`](Canvas::flip_map), zero for identical canvases.
+    pub fn flip(&self, other: &Canvas) -> f64 {
+        let map = self.flip_map(other);
+        let total: f64 = map.bitmap.iter().map(|c| c.red).sum();
+        total / map.bitmap.len().max(1) as f64
+    }
+
+    /// Perceptual error in `[0, 1]` per pixel, modelled after NVIDIA's FLIP.
+    /// Both canvases are filtered with a contrast sensitivity approximation
+    /// before comparing colors, and the color error is amplified where edges
+    /// differ. Expects linear values in `[0, 1]`.
+    pub fn flip_map(&self, other: &Canvas) -> Canvas {
+        self.assert_same_size(other);
+
+        let reference = self.perceived();
+        let test = other.perceived();
+        let max_distance = hyab(
+            lab(Color::new(0.0, 1.0, 0.0)),
+            lab(Color::new(0.0, 0.0, 1.0)),
+        );
+        let color_error = reference.combine(&test, |a, b| {
+            let error = (hyab(a, b) / max_distance).powf(0.7).min(1.0);
+            Color::new(error, error, error)
+        });
+
+        let edges = |c: &Canvas| c.map_scalar(|lab| lab.red / 100.0).sobel();
+        let feature_error = edges(&reference).combine(&edges(&test), |a, b| {
+            // the Sobel magnitude of a unit step is 4
+            let error = ((a.red - b.red).abs() / 4.0 / 2f64.sqrt()).sqrt().min(1.0);
+            Color::new(error, error, error)
+        });
+
+        color_error.combine(&feature_error, |c, f| {
+            let error = c.red.powf(1.0 - f.red);
+            Color::new(error, error, error)
+        })
+    }
+
+    fn assert_same_size(&self, other: &Canvas) {
+        assert_eq!(self.width, other.width); // TODO: real error handling
+        assert_eq!(self.height, other.height);
+    }
+
+    fn combine<F: Fn(Color, Color) -> Color>(&self, other: &Canvas, f: F) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            bitmap: self
+                .bitmap
+                .iter()
+                .zip(&other.bitmap)
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        }
+    }
+
+    // stores a scalar per pixel in all three channels
+    fn map_scalar<F: Fn(Color) -> f64>(&self, f: F) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            bitmap: self
+                .bitmap
+                .iter()
+                .map(|c| {
+                    let value = f(*c);
+                    Color::new(value, value, value)
+                })
+                .collect(),
+        }
+    }
+
+    // L*a*b* after filtering the opponent channels by their contrast sensitivity
+    fn perceived(&self) -> Canvas {
+        let opponent = Canvas {
+            width: self.width,
+            height: self.height,
+            bitmap: self.bitmap.iter().map(|c| to_ycxcz(*c)).collect(),
+        };
+
+        // widths of the achromatic, red-green and blue-yellow sensitivity
+        let sigma = |b: f64| (b / (2.0 * PI * PI)).sqrt() * PIXELS_PER_DEGREE;
+        let luminance = opponent.gaussian_blur(sigma(0.0047), EdgeMode::Clamp);
+        let red_green = opponent.gaussian_blur(sigma(0.0053), EdgeMode::Clamp);
+        let blue_yellow = opponent.gaussian_blur(sigma(0.04), EdgeMode::Clamp);
+
+        let mut result = Canvas::new(self.width, self.height);
+        for i in 0..self.bitmap.len() {
+            let filtered = Color::new(
+                luminance.bitmap[i].red,
+                red_green.bitmap[i].green,
+                blue_yellow.bitmap[i].blue,
+            );
+            let rgb = from_ycxcz(filtered);
+            result.bitmap[i] = lab(rgb.clamp(0.0, 1.0));
+        }
+        result
+    }
+}
+
+/// Fails unless both canvases have the same size and no channel differs by
+/// more than the tolerance, which defaults to `1.0e-5`.
+#[macro_export]
+macro_rules! assert_canvas_approx_eq {
+    ($a:expr, $b:expr) => {
+        $crate::assert_canvas_approx_eq!($a, $b, 1.0e-5)
+    };
+    ($a:expr, $b:expr, $tolerance:expr) => {{
+        let (a, b) = (&$a, &$b);
+        assert!(
+            a.width() == b.width() && a.height() == b.height(),
+            "canvas of {}x{} compared to {}x{}",
+            a.width(),
+            a.height(),
+            b.width(),
+            b.height()
+        );
+        let error = a.max_error(b);
+        assert!(
+            error <= $tolerance,
+            "canvases differ by up to {} (mean {}), more than {}",
+            error,
+            a.mean_error(b),
+            $tolerance
+        );
+    }};
+}
+
+fn to_ycxcz(rgb: Color) -> Color {
+    let (x, y, z) = rgb.to_xyz();
+    let (x, y, z) = (x / D65.0, y / D65.1, z / D65.2);
+    Color::new(116.0 * y - 16.0, 500.0 * (x - y), 200.0 * (y - z))
+}
+
+fn from_ycxcz(ycxcz: Color) -> Color {
+    let y = (ycxcz.red + 16.0) / 116.0;
+    let x = y + ycxcz.green / 500.0;
+    let z = y - ycxcz.blue / 200.0;
+    Color::from_xyz(x * D65.0, y * D65.1, z * D65.2)
+}
+
+// CIE L*a*b* stored as (L, a, b)
+fn lab(rgb: Color) -> Color {
+    let (l, a, b) = rgb.to_lab();
+    Color::new(l, a, b)
+}
+
+// lightness difference plus Euclidean chroma difference
+fn hyab(a: Color, b: Color) -> f64 {
+    (a.red - b.red).abs() + (a.green - b.green).hypot(a.blue - b.blue)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let t = x as f64 / (width - 1) as f64;
+                c.write_pixel(x, y, Color::new(t, 0.5, 1.0 - t));
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn differencing_two_canvases() {
+        let a = gradient(5, 3);
+        let mut b = gradient(5, 3);
+        b.write_pixel(2, 1, Color::new(0.0, 0.75, 0.5));
+        let diff = a.difference(&b);
+
+        assert_eq!(diff.pixel_at(2, 1), Color::new(0.5, 0.25, 0.0));
+        assert_eq!(diff.pixel_at(0, 0), Color::black());
+        assert!((a.max_error(&b) - 0.5).abs() < 1.0e-12);
+        assert!((a.mean_error(&b) - 0.75 / 45.0).abs() < 1.0e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn comparing_canvases_of_different_size() {
+        Canvas::new(2, 2).difference(&Canvas::new(2, 3));
+    }
+
+    #[test]
+    fn psnr_of_a_uniform_error() {
+        let a = Canvas::new(4, 4);
+        let mut b = Canvas::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                b.write_pixel(x, y, Color::new(0.1, 0.1, 0.1));
+            }
+        }
+
+        assert!((a.psnr(&b) - 20.0).abs() < 1.0e-9);
+        assert_eq!(a.psnr(&a), f64::INFINITY);
+    }
+
+    #[test]
+    fn ssim_is_one_for_identical_canvases() {
+        let a = gradient(16, 16);
+        let mut b = gradient(16, 16);
+        b.fill_rectangle(4, 4, 6, 6, Color::new(1.0, 1.0, 1.0));
+
+        assert!((a.ssim(&a) - 1.0).abs() < 1.0e-9);
+        assert!(a.ssim(&b) < 0.9);
+    }
+
+    #[test]
+    fn flip_grows_with_the_difference() {
+        let a = gradient(16, 16);
+        let mut slightly = gradient(16, 16);
+        slightly.write_pixel(8, 8, Color::new(0.6, 0.5, 0.4));
+        let mut very = gradient(16, 16);
+        very.fill_rectangle(4, 4, 8, 8, Color::new(0.0, 1.0, 0.0));
+
+        assert_eq!(a.flip(&a), 0.0);
+        assert!(a.flip(&slightly) > 0.0);
+        assert!(a.flip(&very) > a.flip(&slightly));
+        assert!(a.flip_map(&very).pixel_at(8, 8).red <= 1.0);
+    }
+
+    #[test]
+    fn asserting_approximately_equal_canvases() {
+        let a = gradient(4, 4);
+        let mut b = gradient(4, 4);
+        b.write_pixel(1, 1, b.pixel_at(1, 1) + Color::new(1.0e-7, 0.0, 0.0));
+
+        assert_canvas_approx_eq!(a, b);
+        b.write_pixel(2, 2, Color::new(0.0, 0.0, 0.0));
+        assert_canvas_approx_eq!(a, b, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "canvases differ")]
+    fn asserting_different_canvases() {
+        let a = gradient(4, 4);
+        let b = Canvas::new(4, 4);
+        assert_canvas_approx_eq!(a, b, 0.01);
+    }
+
+    #[test]
+    #[should_panic(expected = "canvases differ")]
+    fn nan_canvases_never_compare_equal() {
+        let mut a = Canvas::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                a.write_pixel(x, y, Color::new(f64::NAN, f64::NAN, f64::NAN));
+            }
+        }
+        assert_eq!(a.max_error(&Canvas::new(4, 4)), f64::INFINITY);
+        assert_eq!(a.max_error(&a), f64::INFINITY);
+
+        assert_canvas_approx_eq!(a, Canvas::new(4, 4), 0.01);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+/// Lays out canvases in a grid, row by row, each with an optional label
+/// underneath. Cells are sized to fit the largest image and label, smaller
+/// images are centered in their cell.
+pub struct ContactSheet<'a> {
+    columns: usize,
+    padding: usize,
+    background: Color,
+    label_color: Color,
+    label_scale: usize,
+    images: Vec<(&'a Canvas, String)>,
+}
+
+impl<'a> ContactSheet<'a> {
+    pub fn new(columns: usize) -> ContactSheet<'a> {
+        assert!(columns > 0); // TODO: real error handling
+
+        ContactSheet {
+            columns,
+            padding: 8,
+            background: Color::new(0.18, 0.18, 0.18),
+            label_color: Color::new(1.0, 1.0, 1.0),
+            label_scale: 1,
+            images: Vec::new(),
+        }
+    }
+
+    /// Space between cells and around the border.
+    pub fn padding(mut self, padding: usize) -> ContactSheet<'a> {
+        self.padding = padding;
+        self
+    }
+
+    pub fn background(mut self, background: Color) -> ContactSheet<'a> {
+        self.background = background;
+        self
+    }
+
+    pub fn label_color(mut self, color: Color) -> ContactSheet<'a> {
+        self.label_color = color;
+        self
+    }
+
+    /// Integer upscaling of the label font.
+    pub fn label_scale(mut self, scale: usize) -> ContactSheet<'a> {
+        self.label_scale = scale;
+        self
+    }
+
+    /// Appends an image, an empty label draws none.
+    pub fn image(mut self, canvas: &'a Canvas, label: &str) -> ContactSheet<'a> {
+        self.images.push((canvas, label.to_string()));
+        self
+    }
+
+    pub fn render(&self) -> Canvas {
+        let labels = self
+            .images
+            .iter()
+            .filter(|(_, label)| !label.is_empty())
+            .map(|(_, label)| Canvas::text_size(label, self.label_scale));
+
+        let (label_width, label_height) = labels.fold((0, 0), |(w, h), (lw, lh)| {
+            (w.max(lw), h.max(lh + self.padding))
+        });
+        let image_width = self.images.iter().map(|(c, _)| c.width).max().unwrap_or(0);
+        let image_height = self.images.iter().map(|(c, _)| c.height).max().unwrap_or(0);
+
+        let cell_width = image_width.max(label_width);
+        let cell_height = image_height + label_height;
+        let rows = self.images.len().div_ceil(self.columns);
+        let columns = self.columns.min(self.images.len());
+
+        let width = columns * cell_width + (columns + 1) * self.padding;
+        let height = rows * cell_height + (rows + 1) * self.padding;
+        let mut sheet = Canvas::filled(width, height, self.background);
+
+        for (index, (canvas, label)) in self.images.iter().enumerate() {
+            let left = self.padding + (index % self.columns) * (cell_width + self.padding);
+            let top = self.padding + (index / self.columns) * (cell_height + self.padding);
+
+            sheet.blit(
+                canvas,
+                (left + (cell_width - canvas.width) / 2) as isize,
+                (top + (image_height - canvas.height) / 2) as isize,
+            );
+            sheet.draw_text(
+                left as isize,
+                (top + image_height + self.padding) as isize,
+                label,
+                self.label_color,
+                self.label_scale,
+            );
+        }
+
+        sheet
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn laying_out_a_grid() {
+        let red = Canvas::filled(4, 4, Color::new(1.0, 0.0, 0.0));
+        let blue = Canvas::filled(2, 2, Color::new(0.0, 0.0, 1.0));
+        let grey = Color::new(0.5, 0.5, 0.5);
+
+        let sheet = ContactSheet::new(2)
+            .padding(1)
+            .background(grey)
+            .image(&red, "")
+            .image(&blue, "")
+            .image(&red, "")
+            .render();
+
+        assert_eq!(sheet.width(), 11);
+        assert_eq!(sheet.height(), 11);
+        assert_eq!(sheet.pixel_at(0, 0), grey);
+        assert_eq!(sheet.pixel_at(1, 1), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(sheet.pixel_at(5, 1), grey);
+        assert_eq!(sheet.pixel_at(7, 2), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(sheet.pixel_at(6, 1), grey);
+        assert_eq!(sheet.pixel_at(4, 9), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(sheet.pixel_at(8, 8), grey);
+    }
+
+    #[test]
+    fn labels_are_drawn_below_the_images() {
+        let image = Canvas::filled(3, 3, Color::new(0.0, 1.0, 0.0));
+        let sheet = ContactSheet::new(4)
+            .padding(2)
+            .background(Color::black())
+            .label_color(Color::new(1.0, 1.0, 1.0))
+            .label_scale(1)
+            .image(&image, "I")
+            .render();
+
+        // a single column wide enough for the 5 pixel glyph
+        assert_eq!(sheet.width(), 9);
+        assert_eq!(sheet.height(), 2 + 3 + 2 + 7 + 2);
+        assert_eq!(sheet.pixel_at(3, 2), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(sheet.pixel_at(4, 7), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn rendering_an_empty_sheet() {
+        let sheet = ContactSheet::new(3).padding(4).render();
+
+        assert_eq!(sheet.width(), 4);
+        assert_eq!(sheet.height(), 4);
+    }
+}
@@ -0,0 +1,190 @@
+// minimal zlib encoder using a single fixed huffman deflate block
+// see: https://www.ietf.org/rfc/rfc1950.txt and https://www.ietf.org/rfc/rfc1951.txt
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+const LENGTH_BASE: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [usize; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // huffman codes are stored most significant bit first
+    fn write_code(&mut self, code: u32, bits: u32) {
+        let reversed = code.reverse_bits() >> (32 - bits);
+        self.write(reversed, bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+
+    fn literal(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn matched(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASE.iter().rposition(|&base| base <= length).unwrap();
+        self.literal(257 + code as u32);
+        self.write((length - LENGTH_BASE[code]) as u32, LENGTH_EXTRA[code]);
+
+        let code = DISTANCE_BASE.iter().rposition(|&base| base <= distance).unwrap();
+        self.write_code(code as u32, 5);
+        self.write((distance - DISTANCE_BASE[code]) as u32, DISTANCE_EXTRA[code]);
+    }
+}
+
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        bytes: vec![0x78, 0x01],
+        buffer: 0,
+        count: 0,
+    };
+
+    writer.write(1, 1); // final block
+    writer.write(1, 2); // fixed huffman codes
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let mut position = 0;
+
+    while position < data.len() {
+        let (length, distance) = longest_match(data, position, &head, &previous);
+
+        let step = if length >= MIN_MATCH {
+            writer.matched(length, distance);
+            length
+        } else {
+            writer.literal(data[position] as u32);
+            1
+        };
+
+        for index in position..position + step {
+            if index + MIN_MATCH <= data.len() {
+                let hash = hash(&data[index..]);
+                previous[index] = head[hash];
+                head[hash] = index;
+            }
+        }
+        position += step;
+    }
+
+    writer.literal(256);
+
+    let mut bytes = writer.finish();
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    bytes
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize;
+    (value.wrapping_mul(2_654_435_761) >> 8) & ((1 << HASH_BITS) - 1)
+}
+
+fn longest_match(data: &[u8], position: usize, head: &[usize], previous: &[usize]) -> (usize, usize) {
+    if position + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let max_length = MAX_MATCH.min(data.len() - position);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(&data[position..])];
+    let mut chain = 0;
+
+    while candidate != usize::MAX && position - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[position..position + max_length])
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        if length > best.0 {
+            best = (length, position - candidate);
+            if length == max_length {
+                break;
+            }
+        }
+
+        candidate = previous[candidate];
+        chain += 1;
+    }
+
+    best
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn adler32_checksum() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn compressing_empty_input() {
+        assert_eq!(zlib_compress(b""), vec![0x78, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn repeated_data_is_compressed() {
+        let data = vec![42_u8; 4096];
+        let compressed = zlib_compress(&data);
+
+        assert!(compressed.len() < 64);
+        assert_eq!(&compressed[compressed.len() - 4..], &adler32(&data).to_be_bytes());
+    }
+}
@@ -0,0 +1,464 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+// Drawing operations clip against the canvas, so shapes may extend past its borders.
+impl Canvas {
+    /// Line including both end points. Steps along the major axis only
+    /// where it crosses the canvas, so far away end points cost nothing.
+    pub fn draw_line(&mut self, x0: isize, y0: isize, x1: isize, y1: isize, color: Color) {
+        let dx = (x1 as i128 - x0 as i128).abs();
+        let dy = (y1 as i128 - y0 as i128).abs();
+
+        if dx >= dy {
+            if let Some((first, last)) = clip_span(x0.min(x1), x0.max(x1), self.width) {
+                for x in first..=last {
+                    self.plot(x, interpolate(x, x0, y0, x1, y1), color);
+                }
+            }
+        } else if let Some((first, last)) = clip_span(y0.min(y1), y0.max(y1), self.height) {
+            for y in first..=last {
+                self.plot(interpolate(y, y0, x0, y1, x1), y, color);
+            }
+        }
+    }
+
+    /// Xiaolin Wu's anti-aliased line, blending `color` over the existing
+    /// pixels. Like `draw_line` it only steps where the line crosses the canvas.
+    pub fn draw_line_antialiased(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, color: Color) {
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (mut x0, mut y0, mut x1, mut y1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+        let plot = |canvas: &mut Canvas, major: f64, minor: f64, coverage: f64| {
+            let (x, y) = if steep {
+                (minor, major)
+            } else {
+                (major, minor)
+            };
+            canvas.blend(x as isize, y as isize, color, coverage);
+        };
+
+        // first end point
+        let x_end = x0.round();
+        let y_end = y0 + gradient * (x_end - x0);
+        let x_gap = 1.0 - fraction(x0 + 0.5);
+        let x_start = x_end;
+        let y_start = y_end;
+        plot(self, x_start, y_end.floor(), (1.0 - fraction(y_end)) * x_gap);
+        plot(self, x_start, y_end.floor() + 1.0, fraction(y_end) * x_gap);
+
+        // second end point
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let x_gap = fraction(x1 + 0.5);
+        let x_stop = x_end;
+        plot(self, x_stop, y_end.floor(), (1.0 - fraction(y_end)) * x_gap);
+        plot(self, x_stop, y_end.floor() + 1.0, fraction(y_end) * x_gap);
+
+        // only the part of the major axis inside the canvas
+        let size = if steep { self.height } else { self.width };
+        let first = (x_start + 1.0).max(0.0);
+        let last = (x_stop - 1.0).min(size as f64 - 1.0);
+        if first <= last {
+            for x in first as isize..=last as isize {
+                let x = x as f64;
+                let intersection = y_start + gradient * (x - x_start);
+                plot(self, x, intersection.floor(), 1.0 - fraction(intersection));
+                plot(self, x, intersection.floor() + 1.0, fraction(intersection));
+            }
+        }
+    }
+
+    /// Outline of the disk filled by `fill_circle`. Only rows crossing the
+    /// canvas are visited, so huge circles cost no more than the canvas.
+    pub fn draw_circle(&mut self, center_x: isize, center_y: isize, radius: isize, color: Color) {
+        for (dy, half_width) in self.circle_rows(center_y, radius) {
+            // the part of this row not covered by the next row further out
+            let inner = circle_half_width(radius as i128, dy.abs() + 1)
+                .map_or(0, |width| width + 1)
+                .min(half_width);
+            let (x, y) = (center_x as i128, center_y as i128 + dy);
+            self.plot_span(y, x + inner, x + half_width, color);
+            self.plot_span(y, x - half_width, x - inner, color);
+        }
+    }
+
+    pub fn fill_circle(&mut self, center_x: isize, center_y: isize, radius: isize, color: Color) {
+        for (dy, half_width) in self.circle_rows(center_y, radius) {
+            let (x, y) = (center_x as i128, center_y as i128 + dy);
+            self.plot_span(y, x - half_width, x + half_width, color);
+        }
+    }
+
+    pub fn draw_rectangle(
+        &mut self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+        color: Color,
+    ) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let right = x.saturating_add((width - 1).min(isize::MAX as usize) as isize);
+        let bottom = y.saturating_add((height - 1).min(isize::MAX as usize) as isize);
+        self.draw_line(x, y, right, y, color);
+        self.draw_line(x, bottom, right, bottom, color);
+        self.draw_line(x, y, x, bottom, color);
+        self.draw_line(right, y, right, bottom, color);
+    }
+
+    pub fn fill_rectangle(
+        &mut self,
+        x: isize,
+        y: isize,
+        width: usize,
+        height: usize,
+        color: Color,
+    ) {
+        let right = x.saturating_add(width.min(isize::MAX as usize) as isize);
+        let bottom = y.saturating_add(height.min(isize::MAX as usize) as isize);
+
+        for py in y.max(0)..bottom.min(self.height as isize) {
+            for px in x.max(0)..right.min(self.width as isize) {
+                self.write_pixel(px as usize, py as usize, color);
+            }
+        }
+    }
+
+    /// Scanline fill using the even-odd rule, sampling at pixel centers.
+    pub fn fill_polygon(&mut self, points: &[(f64, f64)], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let first_row = ((min_y - 0.5).ceil() as isize).max(0);
+        let last_row = ((max_y - 0.5).floor() as isize).min(self.height as isize - 1);
+
+        let mut crossings: Vec<f64> = Vec::new();
+        for row in first_row..=last_row {
+            let scan_y = row as f64 + 0.5;
+
+            crossings.clear();
+            for (i, &(x0, y0)) in points.iter().enumerate() {
+                let (x1, y1) = points[(i + 1) % points.len()];
+                // half open so shared vertices are only counted once
+                if (y0 <= scan_y && scan_y < y1) || (y1 <= scan_y && scan_y < y0) {
+                    crossings.push(x0 + (scan_y - y0) / (y1 - y0) * (x1 - x0));
+                }
+            }
+            crossings.retain(|x| x.is_finite());
+            crossings.sort_by(|a, b| a.total_cmp(b));
+
+            for span in crossings.chunks(2) {
+                if let [start, end] = *span {
+                    let first = ((start - 0.5).ceil() as isize).max(0);
+                    let last = ((end - 0.5).ceil() as isize).min(self.width as isize);
+                    for x in first..last {
+                        self.plot(x, row, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces the 4-connected region of pixels matching the color at `(x, y)`.
+    pub fn flood_fill(&mut self, x: usize, y: usize, color: Color) {
+        let target = self.pixel_at(x, y);
+        if target == color {
+            return;
+        }
+
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if self.pixel_at(x, y) != target {
+                continue;
+            }
+
+            // fill the whole span on this row, queueing the rows above and below
+            let mut left = x;
+            while left > 0 && self.pixel_at(left - 1, y) == target {
+                left -= 1;
+            }
+            let mut right = x;
+            while right + 1 < self.width && self.pixel_at(right + 1, y) == target {
+                right += 1;
+            }
+
+            for px in left..=right {
+                self.write_pixel(px, y, color);
+                if y > 0 && self.pixel_at(px, y - 1) == target {
+                    stack.push((px, y - 1));
+                }
+                if y + 1 < self.height && self.pixel_at(px, y + 1) == target {
+                    stack.push((px, y + 1));
+                }
+            }
+        }
+    }
+
+    // row offsets of a circle that lie on the canvas, with their half widths
+    fn circle_rows(&self, center_y: isize, radius: isize) -> Vec<(i128, i128)> {
+        let (center_y, radius) = (center_y as i128, radius as i128);
+        let first = (-radius).max(-center_y);
+        let last = radius.min(self.height as i128 - 1 - center_y);
+
+        (first..=last)
+            .filter_map(|dy| circle_half_width(radius, dy.abs()).map(|width| (dy, width)))
+            .collect()
+    }
+
+    // pixels from x0 to x1 inclusive on row y, clipped to the canvas
+    fn plot_span(&mut self, y: i128, x0: i128, x1: i128, color: Color) {
+        if y < 0 || y >= self.height as i128 {
+            return;
+        }
+        for x in x0.max(0)..=x1.min(self.width as i128 - 1) {
+            self.write_pixel(x as usize, y as usize, color);
+        }
+    }
+
+    fn plot(&mut self, x: isize, y: isize, color: Color) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.write_pixel(x as usize, y as usize, color);
+        }
+    }
+
+    fn blend(&mut self, x: isize, y: isize, color: Color, alpha: f64) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            let (x, y) = (x as usize, y as usize);
+            let existing = self.pixel_at(x, y);
+            self.write_pixel(x, y, existing * (1.0 - alpha) + color * alpha);
+        }
+    }
+}
+
+// visible part of [start, end] on an axis of `size` pixels
+fn clip_span(start: isize, end: isize, size: usize) -> Option<(isize, isize)> {
+    let first = start.max(0);
+    let last = end.min(size as isize - 1);
+    if first <= last {
+        Some((first, last))
+    } else {
+        None
+    }
+}
+
+// half width of the row `dy` rows away from the center of a circle, `None`
+// beyond the radius
+fn circle_half_width(radius: i128, dy: i128) -> Option<i128> {
+    if dy > radius {
+        return None;
+    }
+    Some(((radius * radius - dy * dy) as f64).sqrt() as i128)
+}
+
+// fractional part towards negative infinity, unlike f64::fract
+fn fraction(value: f64) -> f64 {
+    value - value.floor()
+}
+
+// minor axis coordinate at `t` on the line from (t0, v0) to (t1, v1), rounded
+// to the nearest pixel
+fn interpolate(t: isize, t0: isize, v0: isize, t1: isize, v1: isize) -> isize {
+    if t0 == t1 {
+        return v0;
+    }
+
+    let (mut numerator, mut denominator) = (
+        (t as i128 - t0 as i128) * (v1 as i128 - v0 as i128),
+        t1 as i128 - t0 as i128,
+    );
+    if denominator < 0 {
+        numerator = -numerator;
+        denominator = -denominator;
+    }
+    let offset = (2 * numerator + denominator).div_euclid(2 * denominator);
+    (v0 as i128 + offset) as isize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn count(c: &Canvas, color: Color) -> usize {
+        let mut count = 0;
+        for y in 0..c.height() {
+            for x in 0..c.width() {
+                if c.pixel_at(x, y) == color {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn drawing_a_diagonal_line() {
+        let mut c = Canvas::new(5, 5);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_line(4, 4, 0, 0, white);
+
+        for i in 0..5 {
+            assert_eq!(c.pixel_at(i, i), white);
+        }
+        assert_eq!(count(&c, white), 5);
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_canvas() {
+        let mut c = Canvas::new(4, 4);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_line(-10, 1, 10, 1, white);
+
+        assert_eq!(count(&c, white), 4);
+    }
+
+    #[test]
+    fn antialiased_line_splits_coverage() {
+        let mut c = Canvas::new(6, 4);
+        c.draw_line_antialiased(0.0, 1.5, 5.0, 1.5, Color::new(1.0, 1.0, 1.0));
+
+        assert_eq!(c.pixel_at(2, 1), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(c.pixel_at(2, 2), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(c.pixel_at(2, 0), Color::black());
+    }
+
+    #[test]
+    fn antialiased_lines_are_clipped_to_the_canvas() {
+        let mut c = Canvas::new(4, 4);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_line_antialiased(0.0, -0.3, 3.0, -0.3, white);
+
+        assert_eq!(c.pixel_at(0, 0), Color::new(0.35, 0.35, 0.35));
+        assert_eq!(c.pixel_at(1, 0), Color::new(0.7, 0.7, 0.7));
+        assert_eq!(c.pixel_at(3, 0), Color::new(0.35, 0.35, 0.35));
+        assert_eq!(c.pixel_at(1, 1), Color::black());
+    }
+
+    #[test]
+    fn far_away_antialiased_lines_finish() {
+        let mut c = Canvas::new(4, 4);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_line_antialiased(-1.0e300, 1.5, 1.0e300, 1.5, white);
+        c.draw_line_antialiased(2.5, -1.0e18, 2.5, 1.0e18, white);
+
+        assert_eq!(c.pixel_at(0, 1), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(c.pixel_at(1, 2), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(c.pixel_at(2, 0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(c.pixel_at(3, 3), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn drawing_and_filling_circles() {
+        let mut c = Canvas::new(11, 11);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_circle(5, 5, 4, red);
+
+        assert_eq!(c.pixel_at(9, 5), red);
+        assert_eq!(c.pixel_at(5, 1), red);
+        assert_eq!(c.pixel_at(5, 5), Color::black());
+
+        c.fill_circle(5, 5, 2, red);
+        assert_eq!(c.pixel_at(5, 5), red);
+        assert_eq!(c.pixel_at(7, 5), red);
+        assert_eq!(c.pixel_at(7, 7), Color::black());
+    }
+
+    #[test]
+    fn huge_circles_are_clipped_to_the_canvas() {
+        let mut c = Canvas::new(4, 4);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_circle(2, 1 << 40, 1 << 40, red);
+        // the top of the circle, flattening out right away
+        assert_eq!(count(&c, red), 4);
+        assert_eq!(c.pixel_at(2, 0), red);
+        assert_eq!(c.pixel_at(2, 1), Color::black());
+
+        c.fill_circle(0, 0, isize::MAX, red);
+        assert_eq!(count(&c, red), 16);
+        c.draw_circle(isize::MIN, isize::MIN, isize::MAX, red);
+    }
+
+    #[test]
+    fn drawing_and_filling_rectangles() {
+        let mut c = Canvas::new(6, 6);
+        let green = Color::new(0.0, 1.0, 0.0);
+        c.draw_rectangle(1, 1, 4, 3, green);
+
+        assert_eq!(count(&c, green), 10);
+        assert_eq!(c.pixel_at(2, 2), Color::black());
+
+        c.fill_rectangle(1, 1, 4, 3, green);
+        assert_eq!(count(&c, green), 12);
+    }
+
+    #[test]
+    fn filling_a_polygon() {
+        let mut c = Canvas::new(10, 10);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        c.fill_polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)], blue);
+
+        assert_eq!(count(&c, blue), 16);
+
+        let mut c = Canvas::new(10, 10);
+        c.fill_polygon(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)], blue);
+        assert_eq!(c.pixel_at(1, 1), blue);
+        assert_eq!(c.pixel_at(8, 8), Color::black());
+    }
+
+    #[test]
+    fn flood_filling_a_region() {
+        let mut c = Canvas::new(8, 8);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let red = Color::new(1.0, 0.0, 0.0);
+        c.draw_rectangle(1, 1, 5, 5, white);
+        c.flood_fill(3, 3, red);
+
+        assert_eq!(count(&c, red), 9);
+        assert_eq!(c.pixel_at(0, 0), Color::black());
+
+        c.flood_fill(0, 0, red);
+        assert_eq!(count(&c, red), 64 - 16);
+    }
+
+    #[test]
+    fn far_away_shapes_are_clipped_quickly() {
+        let mut c = Canvas::new(4, 4);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_line(isize::MIN / 2, 2, isize::MAX / 2, 2, white);
+        c.fill_rectangle(isize::MIN / 2, 3, usize::MAX, 1, white);
+
+        assert_eq!(count(&c, white), 8);
+        assert_eq!(c.pixel_at(0, 2), white);
+        assert_eq!(c.pixel_at(3, 3), white);
+
+        let mut c = Canvas::new(4, 4);
+        c.draw_rectangle(1, 1, usize::MAX, usize::MAX, white);
+        c.draw_rectangle(isize::MAX, 0, 10, 10, white);
+        assert_eq!(count(&c, white), 5);
+        assert_eq!(c.pixel_at(3, 3), Color::black());
+    }
+
+    #[test]
+    fn polygons_with_nan_vertices_do_not_panic() {
+        let mut c = Canvas::new(4, 4);
+        let blue = Color::new(0.0, 0.0, 1.0);
+        c.fill_polygon(&[(0.0, 0.0), (f64::NAN, 2.0), (4.0, 4.0), (0.0, 4.0)], blue);
+        c.fill_polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, f64::NAN)], blue);
+    }
+}
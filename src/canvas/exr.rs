@@ -0,0 +1,491 @@
+use crate::canvas::deflate::zlib_compress;
+use crate::canvas::{Canvas, Pixel};
+use crate::color::Color;
+
+// see: https://openexr.com/en/latest/OpenEXRFileLayout.html
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const VERSION: [u8; 4] = [2, 0, 0, 0]; // single part scanline image
+const MIN_RUN: usize = 3;
+const MAX_RUN: usize = 127;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExrPixelType {
+    Half,
+    Float,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExrCompression {
+    None,
+    Rle,
+    Zips,
+    Zip,
+}
+
+impl ExrPixelType {
+    fn id(self) -> i32 {
+        match self {
+            ExrPixelType::Half => 1,
+            ExrPixelType::Float => 2,
+        }
+    }
+}
+
+impl ExrCompression {
+    fn id(self) -> u8 {
+        match self {
+            ExrCompression::None => 0,
+            ExrCompression::Rle => 1,
+            ExrCompression::Zips => 2,
+            ExrCompression::Zip => 3,
+        }
+    }
+
+    fn scanlines_per_chunk(self) -> usize {
+        match self {
+            ExrCompression::Zip => 16,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Source<'a> {
+    Red,
+    Green,
+    Blue,
+    Buffer(&'a [f64]),
+}
+
+/// Writes any pixel type through its `Color`, so f32 buffers need no f64
+/// copy first.
+pub struct ExrWriter<'a, P = Color> {
+    canvas: &'a Canvas<P>,
+    pixel_type: ExrPixelType,
+    compression: ExrCompression,
+    channels: Vec<(String, Source<'a>)>,
+}
+
+impl<'a, P: Pixel> ExrWriter<'a, P> {
+    pub fn new(canvas: &'a Canvas<P>) -> ExrWriter<'a, P> {
+        // the data window is stored as inclusive i32 bounds
+        assert!(canvas.width > 0 && canvas.height > 0); // TODO: real error handling
+        assert!(canvas.width <= i32::MAX as usize && canvas.height <= i32::MAX as usize);
+
+        ExrWriter {
+            canvas,
+            pixel_type: ExrPixelType::Float,
+            compression: ExrCompression::None,
+            channels: vec![
+                (String::from("R"), Source::Red),
+                (String::from("G"), Source::Green),
+                (String::from("B"), Source::Blue),
+            ],
+        }
+    }
+
+    pub fn pixel_type(mut self, pixel_type: ExrPixelType) -> ExrWriter<'a, P> {
+        self.pixel_type = pixel_type;
+        self
+    }
+
+    pub fn compression(mut self, compression: ExrCompression) -> ExrWriter<'a, P> {
+        self.compression = compression;
+        self
+    }
+
+    pub fn alpha(self, alpha: &'a [f64]) -> ExrWriter<'a, P> {
+        self.channel("A", alpha)
+    }
+
+    pub fn channel(mut self, name: &str, values: &'a [f64]) -> ExrWriter<'a, P> {
+        assert!(!name.is_empty() && !name.contains('\0'));
+        assert!(self.channels.iter().all(|(existing, _)| existing != name));
+        assert_eq!(values.len(), self.canvas.width * self.canvas.height);
+
+        self.channels.push((String::from(name), Source::Buffer(values)));
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let width = self.canvas.width;
+        let height = self.canvas.height;
+
+        // channels are stored in alphabetical order, both in the header and the pixel data
+        let mut channels: Vec<&(String, Source)> = self.channels.iter().collect();
+        channels.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut exr: Vec<u8> = Vec::new();
+        exr.extend_from_slice(&MAGIC);
+        exr.extend_from_slice(&VERSION);
+
+        let mut chlist: Vec<u8> = Vec::new();
+        for (name, _) in &channels {
+            chlist.extend_from_slice(name.as_bytes());
+            chlist.push(0);
+            chlist.extend_from_slice(&self.pixel_type.id().to_le_bytes());
+            chlist.extend_from_slice(&[0, 0, 0, 0]); // pLinear and reserved
+            chlist.extend_from_slice(&1_i32.to_le_bytes()); // x sampling
+            chlist.extend_from_slice(&1_i32.to_le_bytes()); // y sampling
+        }
+        chlist.push(0);
+
+        let window = box2i(width, height);
+        attribute(&mut exr, "channels", "chlist", &chlist);
+        attribute(&mut exr, "compression", "compression", &[self.compression.id()]);
+        attribute(&mut exr, "dataWindow", "box2i", &window);
+        attribute(&mut exr, "displayWindow", "box2i", &window);
+        attribute(&mut exr, "lineOrder", "lineOrder", &[0]); // increasing y
+        attribute(&mut exr, "pixelAspectRatio", "float", &1.0_f32.to_le_bytes());
+        attribute(&mut exr, "screenWindowCenter", "v2f", &[0; 8]);
+        attribute(&mut exr, "screenWindowWidth", "float", &1.0_f32.to_le_bytes());
+        exr.push(0);
+
+        let lines = self.compression.scanlines_per_chunk();
+        let chunk_count = height.div_ceil(lines);
+        let table_start = exr.len();
+        exr.resize(table_start + chunk_count * 8, 0);
+
+        for chunk in 0..chunk_count {
+            let offset = exr.len() as u64;
+            exr[table_start + chunk * 8..table_start + chunk * 8 + 8]
+                .copy_from_slice(&offset.to_le_bytes());
+
+            let first_line = chunk * lines;
+            let mut raw: Vec<u8> = Vec::new();
+            for y in first_line..height.min(first_line + lines) {
+                for (_, source) in &channels {
+                    for x in 0..width {
+                        let value = self.sample(*source, x, y);
+                        match self.pixel_type {
+                            ExrPixelType::Half => {
+                                raw.extend_from_slice(&to_half(value as f32).to_le_bytes())
+                            }
+                            ExrPixelType::Float => {
+                                raw.extend_from_slice(&(value as f32).to_le_bytes())
+                            }
+                        }
+                    }
+                }
+            }
+
+            let compressed = match self.compression {
+                ExrCompression::None => raw.clone(),
+                ExrCompression::Rle => rle_compress(&predict(&interleave(&raw))),
+                ExrCompression::Zips | ExrCompression::Zip => {
+                    zlib_compress(&predict(&interleave(&raw)))
+                }
+            };
+
+            // chunks that do not shrink are stored uncompressed
+            let data = if compressed.len() < raw.len() { &compressed } else { &raw };
+
+            exr.extend_from_slice(&(first_line as i32).to_le_bytes());
+            exr.extend_from_slice(&(data.len() as i32).to_le_bytes());
+            exr.extend_from_slice(data);
+        }
+
+        exr
+    }
+
+    fn sample(&self, source: Source, x: usize, y: usize) -> f64 {
+        match source {
+            Source::Red => self.canvas.pixel_at(x, y).to_color().red,
+            Source::Green => self.canvas.pixel_at(x, y).to_color().green,
+            Source::Blue => self.canvas.pixel_at(x, y).to_color().blue,
+            Source::Buffer(values) => values[y * self.canvas.width + x],
+        }
+    }
+}
+
+impl<P: Pixel> Canvas<P> {
+    pub fn to_exr(&self) -> Vec<u8> {
+        ExrWriter::new(self).to_bytes()
+    }
+}
+
+fn attribute(exr: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    exr.extend_from_slice(name.as_bytes());
+    exr.push(0);
+    exr.extend_from_slice(kind.as_bytes());
+    exr.push(0);
+    exr.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    exr.extend_from_slice(value);
+}
+
+fn box2i(width: usize, height: usize) -> Vec<u8> {
+    let mut value: Vec<u8> = Vec::new();
+    value.extend_from_slice(&0_i32.to_le_bytes());
+    value.extend_from_slice(&0_i32.to_le_bytes());
+    value.extend_from_slice(&(width as i32 - 1).to_le_bytes());
+    value.extend_from_slice(&(height as i32 - 1).to_le_bytes());
+    value
+}
+
+// even bytes go to the first half, odd bytes to the second
+fn interleave(raw: &[u8]) -> Vec<u8> {
+    let mut result: Vec<u8> = raw.iter().step_by(2).copied().collect();
+    result.extend(raw.iter().skip(1).step_by(2));
+    result
+}
+
+fn predict(data: &[u8]) -> Vec<u8> {
+    let mut result = data.to_vec();
+    for i in 1..data.len() {
+        result[i] = (data[i] as i32 - data[i - 1] as i32 + 128 + 256) as u8;
+    }
+    result
+}
+
+fn rle_compress(data: &[u8]) -> Vec<u8> {
+    let mut result: Vec<u8> = Vec::new();
+    let mut run_start = 0;
+    let mut run_end = 1;
+
+    while run_start < data.len() {
+        while run_end < data.len()
+            && data[run_start] == data[run_end]
+            && run_end - run_start - 1 < MAX_RUN
+        {
+            run_end += 1;
+        }
+
+        if run_end - run_start >= MIN_RUN {
+            result.push((run_end - run_start - 1) as u8);
+            result.push(data[run_start]);
+            run_start = run_end;
+        } else {
+            while run_end < data.len()
+                && (run_end + 1 >= data.len()
+                    || data[run_end] != data[run_end + 1]
+                    || run_end + 2 >= data.len()
+                    || data[run_end + 1] != data[run_end + 2])
+                && run_end - run_start < MAX_RUN
+            {
+                run_end += 1;
+            }
+
+            result.push((run_start as isize - run_end as isize) as u8);
+            result.extend_from_slice(&data[run_start..run_end]);
+            run_start = run_end;
+        }
+
+        run_end += 1;
+    }
+
+    result
+}
+
+// converts to IEEE 754 half precision, rounding to nearest even
+fn to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        let shift = (14 - exponent) as u32;
+        let mantissa = mantissa | 0x80_0000;
+        return sign | round_shifted(mantissa, shift) as u16;
+    }
+
+    // a carry out of the mantissa correctly bumps the exponent
+    sign | round_shifted((exponent as u32) << 23 | mantissa, 13) as u16
+}
+
+fn round_shifted(value: u32, shift: u32) -> u32 {
+    let truncated = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+
+    if remainder > halfway || (remainder == halfway && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read_i32(data: &[u8], offset: usize) -> i32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&data[offset..offset + 4]);
+        i32::from_le_bytes(bytes)
+    }
+
+    fn find(data: &[u8], needle: &[u8]) -> usize {
+        data.windows(needle.len()).position(|w| w == needle).unwrap()
+    }
+
+    // reverses rle_compress, predict and interleave
+    fn rle_decompress(data: &[u8]) -> Vec<u8> {
+        let mut decoded: Vec<u8> = Vec::new();
+        let mut position = 0;
+        while position < data.len() {
+            let count = data[position] as i8;
+            if count < 0 {
+                let end = position + 1 + (-count) as usize;
+                decoded.extend_from_slice(&data[position + 1..end]);
+                position = end;
+            } else {
+                decoded.extend(std::iter::repeat_n(data[position + 1], count as usize + 1));
+                position += 2;
+            }
+        }
+
+        for i in 1..decoded.len() {
+            decoded[i] = (decoded[i - 1] as i32 + decoded[i] as i32 - 128) as u8;
+        }
+
+        let half = decoded.len().div_ceil(2);
+        let mut raw = Vec::new();
+        for i in 0..decoded.len() {
+            raw.push(if i % 2 == 0 { decoded[i / 2] } else { decoded[half + i / 2] });
+        }
+        raw
+    }
+
+    #[test]
+    fn converting_to_half_floats() {
+        assert_eq!(to_half(0.0), 0x0000);
+        assert_eq!(to_half(-0.0), 0x8000);
+        assert_eq!(to_half(1.0), 0x3c00);
+        assert_eq!(to_half(0.5), 0x3800);
+        assert_eq!(to_half(-2.0), 0xc000);
+        assert_eq!(to_half(65504.0), 0x7bff);
+        assert_eq!(to_half(1.0e6), 0x7c00);
+        assert_eq!(to_half(2.0_f32.powi(-24)), 0x0001);
+        assert_eq!(to_half(1.0 + 2.0_f32.powi(-11)), 0x3c00);
+        assert!(to_half(f32::NAN) & 0x7c00 == 0x7c00 && to_half(f32::NAN) & 0x3ff != 0);
+    }
+
+    #[test]
+    fn run_length_encoding() {
+        assert_eq!(rle_compress(&[7, 7, 7, 7, 1, 2]), vec![3, 7, 0xfe, 1, 2]);
+        assert_eq!(rle_compress(&[5]), vec![0xff, 5]);
+    }
+
+    #[test]
+    fn interleaving_and_predicting() {
+        assert_eq!(interleave(&[1, 2, 3, 4, 5]), vec![1, 3, 5, 2, 4]);
+        assert_eq!(predict(&[10, 12, 11]), vec![10, 130, 127]);
+    }
+
+    #[test]
+    fn writing_the_exr_header() {
+        let mut c = Canvas::new(4, 3);
+        c.write_pixel(1, 2, Color::new(1.0, 2.0, 3.0));
+        let exr = c.to_exr();
+
+        assert_eq!(&exr[0..8], &[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+        let chlist = find(&exr, b"chlist\0") + 11;
+        assert_eq!(&exr[chlist..chlist + 2], b"B\0");
+        assert_eq!(read_i32(&exr, chlist + 2), 2);
+
+        let window = find(&exr, b"dataWindow\0box2i\0") + 21;
+        assert_eq!(read_i32(&exr, window + 8), 3);
+        assert_eq!(read_i32(&exr, window + 12), 2);
+    }
+
+    #[test]
+    fn writing_uncompressed_float_scanlines() {
+        let mut c = Canvas::new(2, 3);
+        c.write_pixel(1, 2, Color::new(1.0, 2.0, 3.0));
+        let depth = [0.0, 0.0, 0.0, 0.0, 0.0, 7.5];
+        let exr = ExrWriter::new(&c).channel("Z", &depth).to_bytes();
+
+        let table = find(&exr, b"screenWindowWidth\0float\0") + 24 + 8 + 1;
+        let offset = read_i32(&exr, table + 2 * 8) as usize;
+        assert_eq!(read_i32(&exr, offset), 2);
+        assert_eq!(read_i32(&exr, offset + 4), 4 * 2 * 4);
+
+        // channel order is B, G, R, Z
+        let line = &exr[offset + 8..];
+        let value = |index: usize| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&line[index * 4..index * 4 + 4]);
+            f32::from_le_bytes(bytes)
+        };
+        assert_eq!(value(1), 3.0);
+        assert_eq!(value(3), 2.0);
+        assert_eq!(value(5), 1.0);
+        assert_eq!(value(7), 7.5);
+        assert_eq!(exr.len(), offset + 8 + 32);
+    }
+
+    #[test]
+    fn compressed_scanlines_are_smaller() {
+        let c = Canvas::new(64, 32);
+        let alpha = vec![1.0; 64 * 32];
+        let plain = ExrWriter::new(&c).alpha(&alpha).pixel_type(ExrPixelType::Half);
+
+        let uncompressed = plain.to_bytes();
+        let rle = ExrWriter::new(&c)
+            .alpha(&alpha)
+            .pixel_type(ExrPixelType::Half)
+            .compression(ExrCompression::Rle)
+            .to_bytes();
+        let zip = ExrWriter::new(&c)
+            .alpha(&alpha)
+            .pixel_type(ExrPixelType::Half)
+            .compression(ExrCompression::Zip)
+            .to_bytes();
+
+        assert!(rle.len() < uncompressed.len() / 4);
+        assert!(zip.len() < rle.len());
+    }
+
+    #[test]
+    fn rle_chunks_decode_to_the_raw_scanline() {
+        let mut c = Canvas::new(16, 2);
+        for x in 0..16 {
+            c.write_pixel(x, 1, Color::new(0.5, 0.25, x as f64));
+        }
+        let exr = ExrWriter::new(&c).compression(ExrCompression::Rle).to_bytes();
+
+        let table = find(&exr, b"screenWindowWidth\0float\0") + 24 + 8 + 1;
+        let offset = read_i32(&exr, table + 8) as usize;
+        assert_eq!(read_i32(&exr, offset), 1);
+        let size = read_i32(&exr, offset + 4) as usize;
+        assert!(size < 16 * 3 * 4);
+
+        let mut raw: Vec<u8> = Vec::new();
+        for channel in 0..3 {
+            for x in 0..16 {
+                let color = c.pixel_at(x, 1);
+                let value = [color.blue, color.green, color.red][channel];
+                raw.extend_from_slice(&(value as f32).to_le_bytes());
+            }
+        }
+        assert_eq!(rle_decompress(&exr[offset + 8..offset + 8 + size]), raw);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_canvases_have_no_data_window() {
+        Canvas::new(0, 0).to_exr();
+    }
+
+    #[test]
+    #[should_panic]
+    fn extra_channels_must_match_canvas_size() {
+        let c = Canvas::new(2, 2);
+        let values = [0.0; 3];
+        ExrWriter::new(&c).channel("Z", &values);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EdgeMode {
+    /// Repeats the border pixel.
+    Clamp,
+    /// Continues from the opposite side.
+    Wrap,
+    /// Reflects around the border, repeating the border pixel once.
+    Mirror,
+}
+
+impl EdgeMode {
+    fn resolve(self, index: isize, length: usize) -> usize {
+        let length = length as isize;
+        let resolved = match self {
+            EdgeMode::Clamp => index.clamp(0, length - 1),
+            EdgeMode::Wrap => index.rem_euclid(length),
+            EdgeMode::Mirror => {
+                let period = 2 * length;
+                let index = index.rem_euclid(period);
+                if index < length {
+                    index
+                } else {
+                    period - 1 - index
+                }
+            }
+        };
+        resolved as usize
+    }
+}
+
+/// A centered kernel with odd dimensions, weights in row-major order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f64>,
+}
+
+impl Kernel {
+    pub fn new(width: usize, height: usize, weights: Vec<f64>) -> Kernel {
+        assert!(width % 2 == 1 && height % 2 == 1);
+        assert_eq!(weights.len(), width * height);
+
+        Kernel {
+            width,
+            height,
+            weights,
+        }
+    }
+}
+
+// Kernels are applied as correlations, without flipping.
+impl Canvas {
+    pub fn convolve(&self, kernel: &Kernel, edge: EdgeMode) -> Canvas {
+        let half_width = (kernel.width / 2) as isize;
+        let half_height = (kernel.height / 2) as isize;
+        let mut result = Canvas::new(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut sum = Color::black();
+                for ky in 0..kernel.height {
+                    let sy = edge.resolve(y as isize + ky as isize - half_height, self.height);
+                    for kx in 0..kernel.width {
+                        let weight = kernel.weights[ky * kernel.width + kx];
+                        if weight != 0.0 {
+                            let sx =
+                                edge.resolve(x as isize + kx as isize - half_width, self.width);
+                            sum += self.bitmap[sy * self.width + sx] * weight;
+                        }
+                    }
+                }
+                result.bitmap[y * self.width + x] = sum;
+            }
+        }
+
+        result
+    }
+
+    /// Applies the outer product of two odd-length 1D kernels as two passes.
+    pub fn convolve_separable(
+        &self,
+        horizontal: &[f64],
+        vertical: &[f64],
+        edge: EdgeMode,
+    ) -> Canvas {
+        let pass = self.convolve(&Kernel::new(horizontal.len(), 1, horizontal.to_vec()), edge);
+        pass.convolve(&Kernel::new(1, vertical.len(), vertical.to_vec()), edge)
+    }
+
+    pub fn gaussian_blur(&self, sigma: f64, edge: EdgeMode) -> Canvas {
+        let weights = gaussian_weights(sigma);
+        self.convolve_separable(&weights, &weights, edge)
+    }
+
+    /// Unsharp mask, adding `amount` times the difference to a blurred copy.
+    pub fn sharpen(&self, sigma: f64, amount: f64) -> Canvas {
+        let blurred = self.gaussian_blur(sigma, EdgeMode::Clamp);
+        let mut result = Canvas::new(self.width, self.height);
+
+        for (index, color) in self.bitmap.iter().enumerate() {
+            result.bitmap[index] = *color + (*color - blurred.bitmap[index]) * amount;
+        }
+        result
+    }
+
+    /// Per channel gradient magnitude of the Sobel operator.
+    pub fn sobel(&self) -> Canvas {
+        let gradient_x =
+            self.convolve_separable(&[-1.0, 0.0, 1.0], &[1.0, 2.0, 1.0], EdgeMode::Clamp);
+        let gradient_y =
+            self.convolve_separable(&[1.0, 2.0, 1.0], &[-1.0, 0.0, 1.0], EdgeMode::Clamp);
+        let mut result = Canvas::new(self.width, self.height);
+
+        for (index, (gx, gy)) in gradient_x.bitmap.iter().zip(&gradient_y.bitmap).enumerate() {
+            result.bitmap[index] = Color::new(
+                gx.red.hypot(gy.red),
+                gx.green.hypot(gy.green),
+                gx.blue.hypot(gy.blue),
+            );
+        }
+        result
+    }
+
+    /// Blurs everything brighter than `threshold` and adds it back scaled by
+    /// `intensity`, giving highlights a glow.
+    pub fn bloom(&self, threshold: f64, sigma: f64, intensity: f64) -> Canvas {
+        let mut bright = Canvas::new(self.width, self.height);
+        for (index, color) in self.bitmap.iter().enumerate() {
+            let l = color.luminance();
+            if l > threshold {
+                bright.bitmap[index] = *color * ((l - threshold) / l);
+            }
+        }
+
+        let glow = bright.gaussian_blur(sigma, EdgeMode::Clamp);
+        let mut result = Canvas::new(self.width, self.height);
+        for (index, color) in self.bitmap.iter().enumerate() {
+            result.bitmap[index] = *color + glow.bitmap[index] * intensity;
+        }
+        result
+    }
+}
+
+// normalized 1D Gaussian covering three standard deviations each side
+fn gaussian_weights(sigma: f64) -> Vec<f64> {
+    if sigma <= 0.0 {
+        return vec![1.0];
+    }
+
+    let radius = (3.0 * sigma).ceil() as isize;
+    let weights: Vec<f64> = (-radius..=radius)
+        .map(|x| (-(x * x) as f64 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn impulse(size: usize) -> Canvas {
+        let mut c = Canvas::new(size, size);
+        c.write_pixel(size / 2, size / 2, Color::new(1.0, 1.0, 1.0));
+        c
+    }
+
+    fn total(c: &Canvas) -> f64 {
+        c.bitmap.iter().map(|color| color.red).sum()
+    }
+
+    #[test]
+    fn resolving_edges() {
+        assert_eq!(EdgeMode::Clamp.resolve(-2, 5), 0);
+        assert_eq!(EdgeMode::Clamp.resolve(7, 5), 4);
+        assert_eq!(EdgeMode::Wrap.resolve(-1, 5), 4);
+        assert_eq!(EdgeMode::Wrap.resolve(6, 5), 1);
+        assert_eq!(EdgeMode::Mirror.resolve(-1, 5), 0);
+        assert_eq!(EdgeMode::Mirror.resolve(-2, 5), 1);
+        assert_eq!(EdgeMode::Mirror.resolve(5, 5), 4);
+        assert_eq!(EdgeMode::Mirror.resolve(6, 5), 3);
+    }
+
+    #[test]
+    fn gaussian_weights_are_normalized() {
+        let weights = gaussian_weights(1.0);
+
+        assert_eq!(weights.len(), 7);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1.0e-12);
+        assert!(weights[3] > weights[2] && weights[2] == weights[4]);
+        assert_eq!(gaussian_weights(0.0), vec![1.0]);
+    }
+
+    #[test]
+    fn convolving_with_a_kernel() {
+        let kernel = Kernel::new(3, 1, vec![0.0, 0.0, 1.0]);
+        let mut c = Canvas::new(3, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+
+        let shifted = c.convolve(&kernel, EdgeMode::Wrap);
+        assert_eq!(shifted.pixel_at(2, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(shifted.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn blurring_preserves_energy() {
+        let blurred = impulse(15).gaussian_blur(1.5, EdgeMode::Wrap);
+
+        assert!((total(&blurred) - 1.0).abs() < 1.0e-9);
+        assert!(blurred.pixel_at(7, 7).red < 1.0);
+        assert_eq!(blurred.pixel_at(6, 7), blurred.pixel_at(8, 7));
+    }
+
+    #[test]
+    fn sharpening_increases_contrast() {
+        let mut c = Canvas::new(12, 1);
+        for x in 6..12 {
+            c.write_pixel(x, 0, Color::new(1.0, 1.0, 1.0));
+        }
+        let sharpened = c.sharpen(1.0, 1.0);
+
+        assert!(sharpened.pixel_at(6, 0).red > 1.0);
+        assert!(sharpened.pixel_at(5, 0).red < 0.0);
+        assert_eq!(sharpened.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn sobel_detects_edges() {
+        let mut c = Canvas::new(4, 4);
+        for y in 0..4 {
+            for x in 2..4 {
+                c.write_pixel(x, y, Color::new(1.0, 1.0, 1.0));
+            }
+        }
+        let edges = c.sobel();
+
+        assert_eq!(edges.pixel_at(1, 1), Color::new(4.0, 4.0, 4.0));
+        assert_eq!(edges.pixel_at(0, 1), Color::black());
+        assert_eq!(edges.pixel_at(3, 2), Color::black());
+    }
+
+    #[test]
+    fn bloom_only_spreads_highlights() {
+        let mut c = impulse(9);
+        c.write_pixel(0, 0, Color::new(0.5, 0.5, 0.5));
+        let bloomed = c.bloom(0.8, 1.0, 1.0);
+
+        assert!(bloomed.pixel_at(5, 4).red > 0.0);
+        assert_eq!(bloomed.pixel_at(0, 0), Color::new(0.5, 0.5, 0.5));
+        assert!(bloomed.pixel_at(4, 4).red > 1.0);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::canvas::Canvas;
+
+impl<P: Copy> Canvas<P> {
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Canvas<P> {
+        assert!(x + width <= self.width); // TODO: real error handling
+        assert!(y + height <= self.height);
+
+        let mut bitmap = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let start = (y + row) * self.width + x;
+            bitmap.extend_from_slice(&self.bitmap[start..start + width]);
+        }
+        Canvas {
+            width,
+            height,
+            bitmap,
+        }
+    }
+
+    /// Copies `source` onto the canvas with its top left corner at `(x, y)`,
+    /// dropping whatever falls outside.
+    pub fn blit(&mut self, source: &Canvas<P>, x: isize, y: isize) {
+        let left = x.max(0);
+        let top = y.max(0);
+        let right = (x + source.width as isize).min(self.width as isize);
+        let bottom = (y + source.height as isize).min(self.height as isize);
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let span = (right - left) as usize;
+        for row in top..bottom {
+            let source_start = (row - y) as usize * source.width + (left - x) as usize;
+            let start = row as usize * self.width + left as usize;
+            self.bitmap[start..start + span]
+                .copy_from_slice(&source.bitmap[source_start..source_start + span]);
+        }
+    }
+
+    pub fn flip_horizontal(&self) -> Canvas<P> {
+        let mut result = self.crop(0, 0, self.width, self.height);
+        for row in result.bitmap.chunks_mut(self.width.max(1)) {
+            row.reverse();
+        }
+        result
+    }
+
+    pub fn flip_vertical(&self) -> Canvas<P> {
+        let mut bitmap = Vec::with_capacity(self.bitmap.len());
+        for row in self.bitmap.chunks(self.width.max(1)).rev() {
+            bitmap.extend_from_slice(row);
+        }
+        Canvas {
+            width: self.width,
+            height: self.height,
+            bitmap,
+        }
+    }
+
+    /// Rotates clockwise by 90 degrees, swapping width and height.
+    pub fn rotate90(&self) -> Canvas<P> {
+        let mut bitmap = Vec::with_capacity(self.bitmap.len());
+        for y in 0..self.width {
+            for x in 0..self.height {
+                bitmap.push(self.pixel_at(y, self.height - 1 - x));
+            }
+        }
+        Canvas {
+            width: self.height,
+            height: self.width,
+            bitmap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+
+    fn numbered(width: usize, height: usize) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                c.write_pixel(x, y, Color::new(x as f64, y as f64, 0.0));
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn cropping_a_canvas() {
+        let c = numbered(5, 4).crop(1, 2, 3, 2);
+
+        assert_eq!(c.width(), 3);
+        assert_eq!(c.height(), 2);
+        assert_eq!(c.pixel_at(0, 0), Color::new(1.0, 2.0, 0.0));
+        assert_eq!(c.pixel_at(2, 1), Color::new(3.0, 3.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn cropping_outside_the_canvas() {
+        numbered(5, 4).crop(3, 0, 3, 1);
+    }
+
+    #[test]
+    fn blitting_is_clipped() {
+        let mut c = Canvas::new(4, 4);
+        c.blit(&numbered(3, 3), -1, 2);
+
+        assert_eq!(c.pixel_at(0, 2), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(c.pixel_at(1, 3), Color::new(2.0, 1.0, 0.0));
+        assert_eq!(c.pixel_at(2, 2), Color::black());
+        assert_eq!(c.pixel_at(0, 1), Color::black());
+
+        c.blit(&numbered(3, 3), 10, 10);
+    }
+
+    #[test]
+    fn flipping_a_canvas() {
+        let c = numbered(3, 2);
+
+        assert_eq!(
+            c.flip_horizontal().pixel_at(0, 1),
+            Color::new(2.0, 1.0, 0.0)
+        );
+        assert_eq!(c.flip_vertical().pixel_at(0, 1), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(c.flip_vertical().pixel_at(2, 0), Color::new(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn rotating_a_canvas_clockwise() {
+        let c = numbered(3, 2).rotate90();
+
+        assert_eq!(c.width(), 2);
+        assert_eq!(c.height(), 3);
+        assert_eq!(c.pixel_at(1, 0), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(c.pixel_at(0, 0), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(c.pixel_at(0, 2), Color::new(2.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,401 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::canvas::{Canvas, Pixel};
+use crate::color::Color;
+
+// see: http://radsite.lbl.gov/radiance/refer/filefmts.pdf
+const SIGNATURE: &[u8] = b"#?";
+const FORMAT: &str = "32-bit_rle_rgbe";
+const MIN_RLE_WIDTH: usize = 8;
+const MAX_RLE_WIDTH: usize = 0x7fff;
+const MAX_RUN: usize = 127;
+const MIN_RUN: usize = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HdrError {
+    InvalidHeader,
+    UnsupportedFormat,
+    UnsupportedOrientation,
+    InvalidScanline,
+    UnexpectedEof,
+}
+
+impl fmt::Display for HdrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            HdrError::InvalidHeader => "invalid radiance header",
+            HdrError::UnsupportedFormat => "unsupported pixel format, expected 32-bit_rle_rgbe",
+            HdrError::UnsupportedOrientation => "unsupported resolution string, expected -Y h +X w",
+            HdrError::InvalidScanline => "invalid run length encoded scanline",
+            HdrError::UnexpectedEof => "unexpected end of file",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl Error for HdrError {}
+
+impl<P: Pixel> Canvas<P> {
+    pub fn to_hdr(&self) -> Vec<u8> {
+        let mut image: Vec<u8> = format!(
+            "#?RADIANCE\nFORMAT={}\n\n-Y {} +X {}\n",
+            FORMAT, self.height, self.width
+        )
+        .into_bytes();
+
+        let use_rle = (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&self.width);
+        let mut scanline: Vec<[u8; 4]> = Vec::with_capacity(self.width);
+
+        for row in self.rows() {
+            scanline.clear();
+            scanline.extend(row.iter().map(|pixel| to_rgbe(pixel.to_color())));
+
+            if use_rle {
+                image.push(2);
+                image.push(2);
+                image.push((self.width >> 8) as u8);
+                image.push((self.width & 255) as u8);
+
+                for component in 0..4 {
+                    let bytes: Vec<u8> = scanline.iter().map(|rgbe| rgbe[component]).collect();
+                    encode_component(&bytes, &mut image);
+                }
+            } else {
+                for rgbe in &scanline {
+                    image.extend_from_slice(rgbe);
+                }
+            }
+        }
+
+        image
+    }
+}
+
+impl Canvas {
+    pub fn from_hdr(data: &[u8]) -> Result<Canvas, HdrError> {
+        let mut reader = Reader { data, position: 0 };
+
+        if !data.starts_with(SIGNATURE) {
+            return Err(HdrError::InvalidHeader);
+        }
+
+        // header lines end with a blank line, the resolution string follows
+        loop {
+            let line = reader.line()?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format.trim() != FORMAT {
+                    return Err(HdrError::UnsupportedFormat);
+                }
+            }
+        }
+
+        let (width, height) = parse_resolution(&reader.line()?)?;
+
+        // runs cover at most MAX_RUN values of one component per byte pair,
+        // so larger claims can't be backed by the remaining data
+        let pixels = width.checked_mul(height).ok_or(HdrError::InvalidHeader)?;
+        let remaining = data.len() - reader.position;
+        if pixels > remaining.saturating_mul(MAX_RUN) / 8 {
+            return Err(HdrError::InvalidHeader);
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        let mut scanline: Vec<[u8; 4]> = vec![[0; 4]; width];
+
+        for row in canvas.rows_mut() {
+            reader.scanline(&mut scanline)?;
+            for (pixel, rgbe) in row.iter_mut().zip(&scanline) {
+                *pixel = from_rgbe(*rgbe);
+            }
+        }
+
+        Ok(canvas)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, HdrError> {
+        let byte = *self.data.get(self.position).ok_or(HdrError::UnexpectedEof)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], HdrError> {
+        let end = self.position + count;
+        let bytes = self.data.get(self.position..end).ok_or(HdrError::UnexpectedEof)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn line(&mut self) -> Result<String, HdrError> {
+        let rest = &self.data[self.position..];
+        let length = rest.iter().position(|&b| b == b'\n').ok_or(HdrError::InvalidHeader)?;
+        self.position += length + 1;
+
+        String::from_utf8(rest[..length].to_vec()).map_err(|_| HdrError::InvalidHeader)
+    }
+
+    fn scanline(&mut self, scanline: &mut [[u8; 4]]) -> Result<(), HdrError> {
+        let width = scanline.len();
+        let start = self.bytes(4.min(width * 4))?;
+        let is_rle = (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&width)
+            && start[0] == 2
+            && start[1] == 2
+            && start[2] & 0x80 == 0;
+
+        if !is_rle {
+            // flat scanline, the bytes already read are the first pixel
+            self.position -= start.len();
+            for rgbe in scanline.iter_mut() {
+                rgbe.copy_from_slice(self.bytes(4)?);
+            }
+            return Ok(());
+        }
+
+        if ((start[2] as usize) << 8 | start[3] as usize) != width {
+            return Err(HdrError::InvalidScanline);
+        }
+
+        for component in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = self.byte()? as usize;
+                if count > 128 {
+                    let count = count - 128;
+                    if x + count > width {
+                        return Err(HdrError::InvalidScanline);
+                    }
+                    let value = self.byte()?;
+                    for rgbe in &mut scanline[x..x + count] {
+                        rgbe[component] = value;
+                    }
+                    x += count;
+                } else {
+                    if count == 0 || x + count > width {
+                        return Err(HdrError::InvalidScanline);
+                    }
+                    for (rgbe, value) in scanline[x..x + count].iter_mut().zip(self.bytes(count)?) {
+                        rgbe[component] = *value;
+                    }
+                    x += count;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_resolution(line: &str) -> Result<(usize, usize), HdrError> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() != 4 {
+        return Err(HdrError::InvalidHeader);
+    }
+    if parts[0] != "-Y" || parts[2] != "+X" {
+        return Err(HdrError::UnsupportedOrientation);
+    }
+
+    let height = parts[1].parse().map_err(|_| HdrError::InvalidHeader)?;
+    let width = parts[3].parse().map_err(|_| HdrError::InvalidHeader)?;
+    Ok((width, height))
+}
+
+fn encode_component(bytes: &[u8], out: &mut Vec<u8>) {
+    let mut position = 0;
+
+    while position < bytes.len() {
+        // look for the next run long enough to be worth encoding
+        let mut run_start = position;
+        let mut run_length = 0;
+        while run_start < bytes.len() {
+            run_length = 1;
+            while run_length < MAX_RUN
+                && run_start + run_length < bytes.len()
+                && bytes[run_start + run_length] == bytes[run_start]
+            {
+                run_length += 1;
+            }
+            if run_length >= MIN_RUN {
+                break;
+            }
+            run_start += run_length;
+        }
+
+        // everything in front of the run is written as literal bytes
+        while position < run_start {
+            let count = (run_start - position).min(128);
+            out.push(count as u8);
+            out.extend_from_slice(&bytes[position..position + count]);
+            position += count;
+        }
+
+        if run_length >= MIN_RUN {
+            out.push((128 + run_length) as u8);
+            out.push(bytes[run_start]);
+            position += run_length;
+        }
+    }
+}
+
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let red = sanitize(color.red);
+    let green = sanitize(color.green);
+    let blue = sanitize(color.blue);
+    let max = red.max(green).max(blue);
+
+    if max < 1.0e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let (mantissa, exponent) = frexp(max);
+    if exponent + 128 > 255 {
+        return [255, 255, 255, 255];
+    }
+    if exponent + 128 < 1 {
+        return [0, 0, 0, 0];
+    }
+
+    let scale = mantissa * 256.0 / max;
+    [
+        (red * scale) as u8,
+        (green * scale) as u8,
+        (blue * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+fn from_rgbe(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::black();
+    }
+
+    let factor = 2.0_f64.powi(rgbe[3] as i32 - (128 + 8));
+    Color::new(
+        rgbe[0] as f64 * factor,
+        rgbe[1] as f64 * factor,
+        rgbe[2] as f64 * factor,
+    )
+}
+
+// RGBE cannot store negative or non-finite values
+fn sanitize(component: f64) -> f64 {
+    if component.is_finite() && component > 0.0 {
+        component
+    } else {
+        0.0
+    }
+}
+
+// splits a positive value into a mantissa in [0.5, 1) and a power of two
+fn frexp(value: f64) -> (f64, i32) {
+    let mut exponent = value.log2().floor() as i32 + 1;
+    let mut mantissa = value / 2.0_f64.powi(exponent);
+
+    if mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1;
+    } else if mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+
+    (mantissa, exponent)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writing_the_hdr_header() {
+        let c = Canvas::new(5, 3);
+        let hdr = c.to_hdr();
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 3 +X 5\n";
+
+        assert_eq!(&hdr[..header.len()], &header[..]);
+        assert_eq!(hdr.len(), header.len() + 5 * 3 * 4);
+    }
+
+    #[test]
+    fn rgbe_preserves_values_above_one() {
+        let color = Color::new(12.5, 0.25, 3.0);
+        let decoded = from_rgbe(to_rgbe(color));
+
+        assert!((decoded.red - 12.5).abs() < 12.5 / 128.0);
+        assert!((decoded.green - 0.25).abs() < 12.5 / 128.0);
+        assert!((decoded.blue - 3.0).abs() < 12.5 / 128.0);
+        assert_eq!(to_rgbe(Color::black()), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color::new(-1.0, f64::NAN, 0.0)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn run_length_encoded_round_trip() {
+        let mut c = Canvas::new(40, 4);
+        for y in 0..4 {
+            for x in 0..40 {
+                let value = if x < 20 { 4.0 } else { x as f64 / 8.0 + y as f64 };
+                c.write_pixel(x, y, Color::new(value, 0.5, 0.0));
+            }
+        }
+
+        let hdr = c.to_hdr();
+        assert!(hdr.len() < 40 * 4 * 4);
+
+        let decoded = Canvas::from_hdr(&hdr).unwrap();
+        assert_eq!(decoded.width(), 40);
+        assert_eq!(decoded.height(), 4);
+        for y in 0..4 {
+            for x in 0..40 {
+                assert_eq!(from_rgbe(to_rgbe(c.pixel_at(x, y))), decoded.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn flat_round_trip() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(2, 1, Color::new(2.0, 1.0, 0.5));
+
+        let decoded = Canvas::from_hdr(&c.to_hdr()).unwrap();
+        assert_eq!(decoded.pixel_at(2, 1), Color::new(2.0, 1.0, 0.5));
+        assert_eq!(decoded.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn reading_invalid_hdr_data() {
+        assert_eq!(Canvas::from_hdr(b"P6\n").err(), Some(HdrError::InvalidHeader));
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n").err(),
+            Some(HdrError::UnsupportedFormat)
+        );
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n+X 1 -Y 1\n").err(),
+            Some(HdrError::UnsupportedOrientation)
+        );
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y 1 +X 2\n\x80\x80").err(),
+            Some(HdrError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn rejecting_oversized_resolutions() {
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y 99999999999 +X 99999999999\n").err(),
+            Some(HdrError::InvalidHeader)
+        );
+        assert_eq!(
+            Canvas::from_hdr(b"#?RADIANCE\n\n-Y 100000 +X 100000\n\x02\x02").err(),
+            Some(HdrError::InvalidHeader)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::slice::{ChunksExact, ChunksExactMut, Iter, IterMut};
+
+use crate::canvas::Canvas;
+
+impl<P> Canvas<P> {
+    /// Pixels in row-major order.
+    pub fn pixels(&self) -> Iter<'_, P> {
+        self.bitmap.iter()
+    }
+
+    pub fn pixels_mut(&mut self) -> IterMut<'_, P> {
+        self.bitmap.iter_mut()
+    }
+
+    /// Rows from top to bottom, each `width` pixels long.
+    pub fn rows(&self) -> ChunksExact<'_, P> {
+        self.bitmap.chunks_exact(self.width.max(1))
+    }
+
+    pub fn rows_mut(&mut self) -> ChunksExactMut<'_, P> {
+        self.bitmap.chunks_exact_mut(self.width.max(1))
+    }
+
+    /// Pixels in row-major order together with their `(x, y)` coordinates.
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &P)> {
+        let width = self.width;
+        self.bitmap
+            .iter()
+            .enumerate()
+            .map(move |(index, pixel)| (index % width, index / width, pixel))
+    }
+
+    pub fn enumerate_pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut P)> {
+        let width = self.width;
+        self.bitmap
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, pixel)| (index % width, index / width, pixel))
+    }
+
+    /// Splits the canvas into disjoint tiles of at most `tile_width` by
+    /// `tile_height` pixels, row by row. The tiles borrow the canvas mutably,
+    /// so they can be handed to separate threads and filled independently.
+    pub fn tiles_mut(&mut self, tile_width: usize, tile_height: usize) -> Vec<Tile<'_, P>> {
+        assert!(tile_width > 0 && tile_height > 0); // TODO: real error handling
+
+        let width = self.width;
+        let mut tiles = Vec::new();
+        if width == 0 {
+            return tiles;
+        }
+
+        for (band, rows) in self.bitmap.chunks_mut(width * tile_height).enumerate() {
+            let top = band * tile_height;
+            let first = tiles.len();
+            for x in (0..width).step_by(tile_width) {
+                tiles.push(Tile {
+                    x,
+                    y: top,
+                    width: tile_width.min(width - x),
+                    rows: Vec::new(),
+                });
+            }
+
+            for row in rows.chunks_mut(width) {
+                for (offset, span) in row.chunks_mut(tile_width).enumerate() {
+                    tiles[first + offset].rows.push(span);
+                }
+            }
+        }
+
+        tiles
+    }
+}
+
+/// A rectangular region of a canvas borrowed mutably by
+/// [`Canvas::tiles_mut`]. Coordinates passed to it are relative to the tile.
+pub struct Tile<'a, P> {
+    x: usize,
+    y: usize,
+    width: usize,
+    rows: Vec<&'a mut [P]>,
+}
+
+impl<'a, P: Copy> Tile<'a, P> {
+    /// Position of the top left corner on the canvas.
+    pub fn origin(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn write_pixel(&mut self, x: usize, y: usize, pixel: P) {
+        assert!(x < self.width); // TODO: real error handling
+
+        self.rows[y][x] = pixel;
+    }
+
+    pub fn pixel_at(&self, x: usize, y: usize) -> P {
+        assert!(x < self.width); // TODO: real error handling
+
+        self.rows[y][x]
+    }
+
+    pub fn rows_mut(&mut self) -> IterMut<'_, &'a mut [P]> {
+        self.rows.iter_mut()
+    }
+
+    /// Pixels together with their `(x, y)` coordinates on the canvas.
+    pub fn enumerate_pixels_mut(
+        &mut self,
+    ) -> Box<dyn Iterator<Item = (usize, usize, &mut P)> + '_> {
+        let (left, top) = (self.x, self.y);
+        Box::new(self.rows.iter_mut().enumerate().flat_map(move |(y, row)| {
+            row.iter_mut()
+                .enumerate()
+                .map(move |(x, pixel)| (left + x, top + y, pixel))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+
+    #[test]
+    fn iterating_over_pixels_and_rows() {
+        let mut c = Canvas::new(3, 2);
+        for (x, y, pixel) in c.enumerate_pixels_mut() {
+            *pixel = Color::new(x as f64, y as f64, 0.0);
+        }
+
+        assert_eq!(c.pixel_at(2, 1), Color::new(2.0, 1.0, 0.0));
+        assert_eq!(c.pixels().count(), 6);
+        assert_eq!(c.rows().count(), 2);
+        assert_eq!(c.rows().nth(1).unwrap()[0], Color::new(0.0, 1.0, 0.0));
+
+        for row in c.rows_mut() {
+            row[0] = Color::new(1.0, 1.0, 1.0);
+        }
+        for pixel in c.pixels_mut() {
+            *pixel *= 2.0;
+        }
+        assert_eq!(c.pixel_at(0, 1), Color::new(2.0, 2.0, 2.0));
+
+        let (x, y, pixel) = c.enumerate_pixels().last().unwrap();
+        assert_eq!((x, y, *pixel), (2, 1, Color::new(4.0, 2.0, 0.0)));
+    }
+
+    #[test]
+    fn splitting_into_tiles() {
+        let mut c = Canvas::filled(5, 3, 0u8);
+        let tiles = c.tiles_mut(2, 2);
+
+        let layout: Vec<_> = tiles
+            .iter()
+            .map(|tile| (tile.origin(), tile.width(), tile.height()))
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                ((0, 0), 2, 2),
+                ((2, 0), 2, 2),
+                ((4, 0), 1, 2),
+                ((0, 2), 2, 1),
+                ((2, 2), 2, 1),
+                ((4, 2), 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn filling_tiles_from_threads() {
+        let mut c = Canvas::new(7, 5);
+
+        std::thread::scope(|scope| {
+            for mut tile in c.tiles_mut(3, 2) {
+                scope.spawn(move || {
+                    for (x, y, pixel) in tile.enumerate_pixels_mut() {
+                        *pixel = Color::new(x as f64, y as f64, 1.0);
+                    }
+                    tile.write_pixel(0, 0, Color::black());
+                });
+            }
+        });
+
+        assert_eq!(c.pixel_at(5, 4), Color::new(5.0, 4.0, 1.0));
+        assert_eq!(c.pixel_at(4, 1), Color::new(4.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(3, 2), Color::black());
+        assert_eq!(c.pixel_at(3, 1), Color::new(3.0, 1.0, 1.0));
+    }
+}
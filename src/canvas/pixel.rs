@@ -0,0 +1,106 @@
+use crate::color::Color;
+
+/// A value stored per pixel in a [`Canvas`](crate::Canvas), convertible to and
+/// from `Color`.
+pub trait Pixel: Copy {
+    fn to_color(self) -> Color;
+    fn from_color(color: Color) -> Self;
+}
+
+impl Pixel for Color {
+    fn to_color(self) -> Color {
+        self
+    }
+
+    fn from_color(color: Color) -> Color {
+        color
+    }
+}
+
+/// Single channel, e.g. for depth. Converted colors keep their luminance.
+impl Pixel for f32 {
+    fn to_color(self) -> Color {
+        let value = f64::from(self);
+        Color::new(value, value, value)
+    }
+
+    fn from_color(color: Color) -> f32 {
+        color.luminance() as f32
+    }
+}
+
+/// RGB in single precision, half the size of `Color`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rgb32 {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+}
+
+impl Rgb32 {
+    pub fn new(red: f32, green: f32, blue: f32) -> Rgb32 {
+        Rgb32 { red, green, blue }
+    }
+}
+
+impl Pixel for Rgb32 {
+    fn to_color(self) -> Color {
+        Color::new(self.red.into(), self.green.into(), self.blue.into())
+    }
+
+    fn from_color(color: Color) -> Rgb32 {
+        Rgb32::new(color.red as f32, color.green as f32, color.blue as f32)
+    }
+}
+
+/// Single precision RGB with straight (not premultiplied) alpha. Converting
+/// to `Color` drops the alpha, converting from it is opaque.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rgba {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Rgba {
+    pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Rgba {
+        Rgba {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+}
+
+impl Pixel for Rgba {
+    fn to_color(self) -> Color {
+        Color::new(self.red.into(), self.green.into(), self.blue.into())
+    }
+
+    fn from_color(color: Color) -> Rgba {
+        Rgba::new(color.red as f32, color.green as f32, color.blue as f32, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn single_precision_pixels_are_smaller() {
+        assert_eq!(
+            std::mem::size_of::<Rgb32>() * 2,
+            std::mem::size_of::<Color>()
+        );
+        assert_eq!(std::mem::size_of::<f32>() * 6, std::mem::size_of::<Color>());
+    }
+
+    #[test]
+    fn converting_depth_values() {
+        assert_eq!(2.0f32.to_color(), Color::new(2.0, 2.0, 2.0));
+        assert_eq!(f32::from_color(Color::new(1.0, 1.0, 1.0)), 1.0);
+        assert_eq!(f32::from_color(Color::new(0.0, 1.0, 0.0)), 0.7152);
+    }
+}
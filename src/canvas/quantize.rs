@@ -0,0 +1,341 @@
+use std::sync::OnceLock;
+
+use crate::canvas::{Canvas, Pixel};
+use crate::color::{Color, TransferFunction};
+
+const BAYER_SIZE: usize = 8;
+const BLUE_NOISE_SIZE: usize = 64;
+const BLUE_NOISE_SIGMA: f64 = 1.5;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Quantization {
+    /// Rounds to the nearest level like `Color::convert_component`.
+    Round,
+    /// Ordered dithering with an 8x8 Bayer matrix.
+    Bayer,
+    /// Ordered dithering with a 64x64 void-and-cluster blue noise mask.
+    BlueNoise,
+    /// Floyd-Steinberg error diffusion in serpentine order.
+    FloydSteinberg,
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Encodes the canvas with `transfer` and reduces it to 8 bits per channel,
+    /// returning RGB triples in row-major order.
+    pub fn quantize(&self, transfer: TransferFunction, quantization: Quantization) -> Vec<[u8; 3]> {
+        let encoded: Vec<Color> = self
+            .bitmap
+            .iter()
+            .map(|pixel| transfer.encode_color(pixel.to_color()))
+            .collect();
+
+        match quantization {
+            Quantization::Round => encoded.iter().map(|color| round(*color)).collect(),
+            Quantization::Bayer => self.ordered(&encoded, bayer_threshold),
+            Quantization::BlueNoise => {
+                let mask = blue_noise();
+                self.ordered(&encoded, |x, y| {
+                    mask[(y % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x % BLUE_NOISE_SIZE]
+                })
+            }
+            Quantization::FloydSteinberg => self.floyd_steinberg(encoded),
+        }
+    }
+
+    fn ordered<F: Fn(usize, usize) -> f64>(&self, encoded: &[Color], threshold: F) -> Vec<[u8; 3]> {
+        encoded
+            .iter()
+            .enumerate()
+            .map(|(index, color)| {
+                let offset = threshold(index % self.width, index / self.width);
+                let level = |component: f64| clamp_level((component * 255.0 + offset).floor());
+                [level(color.red), level(color.green), level(color.blue)]
+            })
+            .collect()
+    }
+
+    fn floyd_steinberg(&self, mut encoded: Vec<Color>) -> Vec<[u8; 3]> {
+        let mut result = vec![[0; 3]; encoded.len()];
+
+        for y in 0..self.height {
+            let left_to_right = y % 2 == 0;
+            for i in 0..self.width {
+                let x = if left_to_right { i } else { self.width - 1 - i };
+                let index = y * self.width + x;
+                let color = encoded[index];
+                result[index] = round(color);
+
+                // NaN and infinite pixels would poison every later neighbour
+                if !color.is_finite() {
+                    continue;
+                }
+                // only the error within the displayable range is diffused
+                let color = color.clamp(0.0, 1.0);
+                let quantized = round(color);
+
+                let error = color
+                    - Color::new(
+                        quantized[0] as f64 / 255.0,
+                        quantized[1] as f64 / 255.0,
+                        quantized[2] as f64 / 255.0,
+                    );
+
+                // neighbours mirror when scanning right to left
+                let forward = |dx: isize| -> Option<usize> {
+                    let dx = if left_to_right { dx } else { -dx };
+                    let x = x as isize + dx;
+                    if x >= 0 && (x as usize) < self.width {
+                        Some(x as usize)
+                    } else {
+                        None
+                    }
+                };
+
+                if let Some(next) = forward(1) {
+                    encoded[y * self.width + next] += error * (7.0 / 16.0);
+                }
+                if y + 1 < self.height {
+                    let below = (y + 1) * self.width;
+                    for (dx, weight) in [(-1, 3.0 / 16.0), (0, 5.0 / 16.0), (1, 1.0 / 16.0)] {
+                        if let Some(x) = forward(dx) {
+                            encoded[below + x] += error * weight;
+                        }
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+fn round(color: Color) -> [u8; 3] {
+    [
+        Color::convert_component(color.red),
+        Color::convert_component(color.green),
+        Color::convert_component(color.blue),
+    ]
+}
+
+fn clamp_level(level: f64) -> u8 {
+    if level.is_nan() {
+        0
+    } else {
+        level.clamp(0.0, 255.0) as u8
+    }
+}
+
+/// Threshold in `[0, 1)` of the tiled 8x8 Bayer matrix at `(x, y)`.
+pub(crate) fn bayer_threshold(x: usize, y: usize) -> f64 {
+    (bayer(x % BAYER_SIZE, y % BAYER_SIZE) as f64 + 0.5) / (BAYER_SIZE * BAYER_SIZE) as f64
+}
+
+// the finest 2x2 position carries the largest weight in the Bayer index
+fn bayer(x: usize, y: usize) -> usize {
+    let mut value = 0;
+    let mut weight = BAYER_SIZE * BAYER_SIZE / 4;
+    let (mut x, mut y) = (x, y);
+
+    while weight > 0 {
+        let quadrant = match (x & 1, y & 1) {
+            (0, 0) => 0,
+            (1, 1) => 1,
+            (1, 0) => 2,
+            _ => 3,
+        };
+        value += quadrant * weight;
+        x >>= 1;
+        y >>= 1;
+        weight /= 4;
+    }
+
+    value
+}
+
+fn blue_noise() -> &'static [f64] {
+    static MASK: OnceLock<Vec<f64>> = OnceLock::new();
+    MASK.get_or_init(void_and_cluster)
+}
+
+// see: Ulichney, "The void-and-cluster method for dither array generation"
+fn void_and_cluster() -> Vec<f64> {
+    let size = BLUE_NOISE_SIZE;
+    let count = size * size;
+
+    let mut kernel = vec![0.0; count];
+    for dy in 0..size {
+        for dx in 0..size {
+            let x = dx.min(size - dx) as f64;
+            let y = dy.min(size - dy) as f64;
+            kernel[dy * size + dx] =
+                (-(x * x + y * y) / (2.0 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA)).exp();
+        }
+    }
+
+    let update = |energy: &mut [f64], index: usize, sign: f64| {
+        let (px, py) = (index % size, index / size);
+        for y in 0..size {
+            for x in 0..size {
+                let dx = (x + size - px) % size;
+                let dy = (y + size - py) % size;
+                energy[y * size + x] += sign * kernel[dy * size + dx];
+            }
+        }
+    };
+
+    // first pixel with the highest or lowest energy among those set to `value`
+    let extreme = |energy: &[f64], pattern: &[bool], value: bool, largest: bool| {
+        let mut best: Option<usize> = None;
+        for index in (0..count).filter(|&i| pattern[i] == value) {
+            let better = match best {
+                None => true,
+                Some(b) if largest => energy[index] > energy[b],
+                Some(b) => energy[index] < energy[b],
+            };
+            if better {
+                best = Some(index);
+            }
+        }
+        best.unwrap()
+    };
+
+    // deterministic initial pattern with roughly a tenth of the pixels set
+    let mut pattern = vec![false; count];
+    let mut energy = vec![0.0; count];
+    let mut seed: u32 = 0x2545_f491;
+    let mut ones = 0;
+    while ones < count / 10 {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        let index = seed as usize % count;
+        if !pattern[index] {
+            pattern[index] = true;
+            update(&mut energy, index, 1.0);
+            ones += 1;
+        }
+    }
+
+    // move points from the tightest clusters into the largest voids until
+    // stable, giving up after one pass worth of swaps
+    for _ in 0..count {
+        let cluster = extreme(&energy, &pattern, true, true);
+        pattern[cluster] = false;
+        update(&mut energy, cluster, -1.0);
+
+        let void = extreme(&energy, &pattern, false, false);
+        pattern[void] = true;
+        update(&mut energy, void, 1.0);
+
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut rank = vec![0; count];
+
+    let mut removing = pattern.clone();
+    let mut removing_energy = energy.clone();
+    for r in (0..ones).rev() {
+        let cluster = extreme(&removing_energy, &removing, true, true);
+        removing[cluster] = false;
+        update(&mut removing_energy, cluster, -1.0);
+        rank[cluster] = r;
+    }
+
+    for r in ones..count {
+        let void = extreme(&energy, &pattern, false, false);
+        pattern[void] = true;
+        update(&mut energy, void, 1.0);
+        rank[void] = r;
+    }
+
+    // thresholds sit in the middle of their bucket
+    rank.iter()
+        .map(|&r| (r as f64 + 0.5) / count as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn average(levels: &[[u8; 3]]) -> f64 {
+        levels.iter().map(|level| level[0] as f64).sum::<f64>() / levels.len() as f64
+    }
+
+    fn flat_canvas(value: f64) -> Canvas {
+        let mut c = Canvas::new(64, 64);
+        for y in 0..64 {
+            for x in 0..64 {
+                c.write_pixel(x, y, Color::new(value, value, value));
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn rounding_matches_convert_component() {
+        let c = flat_canvas(0.5);
+        let levels = c.quantize(TransferFunction::Linear, Quantization::Round);
+
+        assert!(levels
+            .iter()
+            .all(|level| level[1] == Color::convert_component(0.5)));
+    }
+
+    #[test]
+    fn bayer_matrix_contains_every_index() {
+        let mut indices: Vec<usize> = (0..64).map(|i| bayer(i % 8, i / 8)).collect();
+        assert_eq!(bayer(0, 0), 0);
+        assert_eq!(bayer(1, 1), 16);
+
+        indices.sort();
+        assert_eq!(indices, (0..64).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn blue_noise_mask_is_a_permutation() {
+        let mut ranks: Vec<usize> = blue_noise().iter().map(|t| (t * 4096.0) as usize).collect();
+
+        ranks.sort();
+        assert_eq!(ranks, (0..4096).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn dithering_preserves_the_average_level() {
+        // a value between two levels that rounding would always push down
+        let c = flat_canvas(100.3 / 255.0);
+        let strategies = [
+            Quantization::Bayer,
+            Quantization::BlueNoise,
+            Quantization::FloydSteinberg,
+        ];
+
+        assert_eq!(
+            average(&c.quantize(TransferFunction::Linear, Quantization::Round)),
+            100.0
+        );
+        for strategy in strategies.iter() {
+            let levels = c.quantize(TransferFunction::Linear, *strategy);
+
+            assert!((average(&levels) - 100.3).abs() < 0.02, "{:?}", strategy);
+            assert!(levels
+                .iter()
+                .all(|level| level[0] == 100 || level[0] == 101));
+        }
+    }
+
+    #[test]
+    fn error_diffusion_ignores_nan_and_clips_hdr_pixels() {
+        let mut c = flat_canvas(0.5);
+        c.write_pixel(0, 0, Color::new(f64::NAN, f64::NAN, f64::NAN));
+        c.write_pixel(1, 0, Color::new(10.0, 10.0, 10.0));
+        let levels = c.quantize(TransferFunction::Linear, Quantization::FloydSteinberg);
+
+        assert_eq!(levels[1], [255, 255, 255]);
+        assert!(levels[2..]
+            .iter()
+            .all(|level| level[0] == 127 || level[0] == 128));
+    }
+}
@@ -0,0 +1,230 @@
+use std::f64::consts::PI;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResizeFilter {
+    Box,
+    Bilinear,
+    /// Catmull-Rom cubic (B = 0, C = 0.5).
+    Bicubic,
+    /// Three lobe windowed sinc.
+    Lanczos,
+    /// Mitchell-Netravali cubic (B = C = 1/3).
+    Mitchell,
+}
+
+impl ResizeFilter {
+    fn support(self) -> f64 {
+        match self {
+            ResizeFilter::Box => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Bicubic | ResizeFilter::Mitchell => 2.0,
+            ResizeFilter::Lanczos => 3.0,
+        }
+    }
+
+    fn weight(self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Box => {
+                if x <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            ResizeFilter::Bicubic => cubic(x, 0.0, 0.5),
+            ResizeFilter::Mitchell => cubic(x, 1.0 / 3.0, 1.0 / 3.0),
+            ResizeFilter::Lanczos => {
+                if x < 1.0e-8 {
+                    1.0
+                } else if x < 3.0 {
+                    3.0 * (PI * x).sin() * (PI * x / 3.0).sin() / (PI * PI * x * x)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+impl Canvas {
+    /// Resamples to the given size, widening the filter when shrinking so every
+    /// source pixel contributes.
+    pub fn resize(&self, width: usize, height: usize, filter: ResizeFilter) -> Canvas {
+        let columns = contributions(self.width, width, filter);
+        let rows = contributions(self.height, height, filter);
+
+        let mut horizontal = Canvas::new(width, self.height);
+        for y in 0..self.height {
+            for (x, weights) in columns.iter().enumerate() {
+                let color = weights
+                    .iter()
+                    .fold(Color::black(), |sum, &(index, weight)| {
+                        sum + self.bitmap[y * self.width + index] * weight
+                    });
+                horizontal.bitmap[y * width + x] = color;
+            }
+        }
+
+        let mut result = Canvas::new(width, height);
+        for (y, weights) in rows.iter().enumerate() {
+            for x in 0..width {
+                let color = weights
+                    .iter()
+                    .fold(Color::black(), |sum, &(index, weight)| {
+                        sum + horizontal.bitmap[index * width + x] * weight
+                    });
+                result.bitmap[y * width + x] = color;
+            }
+        }
+
+        result
+    }
+}
+
+// normalized source weights for every target pixel along one axis
+fn contributions(source: usize, target: usize, filter: ResizeFilter) -> Vec<Vec<(usize, f64)>> {
+    if source == 0 {
+        return vec![Vec::new(); target];
+    }
+
+    let ratio = source as f64 / target as f64;
+    let filter_scale = ratio.max(1.0);
+    let support = filter.support() * filter_scale;
+
+    (0..target)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * ratio - 0.5;
+            let first = (center - support).floor() as isize;
+            let last = (center + support).ceil() as isize;
+
+            let mut weights: Vec<(usize, f64)> = Vec::new();
+            for j in first..=last {
+                let weight = filter.weight((j as f64 - center) / filter_scale);
+                if weight != 0.0 {
+                    // edges are clamped, repeating the border pixels
+                    let index = j.clamp(0, source as isize - 1) as usize;
+                    weights.push((index, weight));
+                }
+            }
+
+            let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+            if total == 0.0 {
+                let nearest = (center.round().max(0.0) as usize).min(source - 1);
+                return vec![(nearest, 1.0)];
+            }
+            weights
+                .into_iter()
+                .map(|(index, weight)| (index, weight / total))
+                .collect()
+        })
+        .collect()
+}
+
+fn cubic(x: f64, b: f64, c: f64) -> f64 {
+    if x < 1.0 {
+        ((12.0 - 9.0 * b - 6.0 * c) * x * x * x
+            + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+            + (6.0 - 2.0 * b))
+            / 6.0
+    } else if x < 2.0 {
+        ((-b - 6.0 * c) * x * x * x
+            + (6.0 * b + 30.0 * c) * x * x
+            + (-12.0 * b - 48.0 * c) * x
+            + (8.0 * b + 24.0 * c))
+            / 6.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILTERS: [ResizeFilter; 5] = [
+        ResizeFilter::Box,
+        ResizeFilter::Bilinear,
+        ResizeFilter::Bicubic,
+        ResizeFilter::Lanczos,
+        ResizeFilter::Mitchell,
+    ];
+
+    #[test]
+    fn filters_interpolate_at_the_sample() {
+        assert_eq!(ResizeFilter::Bicubic.weight(0.0), 1.0);
+        assert_eq!(ResizeFilter::Bicubic.weight(1.0), 0.0);
+        assert_eq!(ResizeFilter::Lanczos.weight(0.0), 1.0);
+        assert!(ResizeFilter::Lanczos.weight(2.0).abs() < 1.0e-12);
+        assert!((ResizeFilter::Mitchell.weight(0.0) - 8.0 / 9.0).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn resizing_a_flat_canvas_keeps_its_color() {
+        let mut c = Canvas::new(7, 5);
+        for y in 0..5 {
+            for x in 0..7 {
+                c.write_pixel(x, y, Color::new(0.25, 0.5, 1.0));
+            }
+        }
+
+        for filter in FILTERS.iter() {
+            let up = c.resize(20, 13, *filter);
+            let down = c.resize(3, 2, *filter);
+
+            assert_eq!(up.pixel_at(19, 12), Color::new(0.25, 0.5, 1.0));
+            assert_eq!(down.pixel_at(1, 1), Color::new(0.25, 0.5, 1.0));
+        }
+    }
+
+    #[test]
+    fn box_filter_averages_blocks_when_halving() {
+        let mut c = Canvas::new(4, 2);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        c.write_pixel(3, 1, Color::new(0.0, 0.0, 1.0));
+
+        let half = c.resize(2, 1, ResizeFilter::Box);
+        assert_eq!(half.pixel_at(0, 0), Color::new(0.25, 0.0, 0.0));
+        assert_eq!(half.pixel_at(1, 0), Color::new(0.0, 0.0, 0.25));
+    }
+
+    #[test]
+    fn resizing_to_the_same_size_is_identity_for_interpolating_filters() {
+        let mut c = Canvas::new(5, 5);
+        c.write_pixel(2, 3, Color::new(1.0, 0.5, 0.0));
+
+        for filter in [
+            ResizeFilter::Box,
+            ResizeFilter::Bilinear,
+            ResizeFilter::Bicubic,
+            ResizeFilter::Lanczos,
+        ]
+        .iter()
+        {
+            let same = c.resize(5, 5, *filter);
+            assert_eq!(
+                same.pixel_at(2, 3),
+                Color::new(1.0, 0.5, 0.0),
+                "{:?}",
+                filter
+            );
+            assert_eq!(same.pixel_at(2, 2), Color::black(), "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn bilinear_upscaling_interpolates_between_pixels() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(1, 0, Color::new(1.0, 1.0, 1.0));
+
+        let up = c.resize(4, 1, ResizeFilter::Bilinear);
+        assert_eq!(up.pixel_at(0, 0), Color::black());
+        assert_eq!(up.pixel_at(1, 0), Color::new(0.25, 0.25, 0.25));
+        assert_eq!(up.pixel_at(2, 0), Color::new(0.75, 0.75, 0.75));
+        assert_eq!(up.pixel_at(3, 0), Color::new(1.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::canvas::Canvas;
+use crate::color::{Color, ColorRamp};
+
+/// Counts of values falling into equally sized bins between `min` and `max`.
+/// Values outside the range are counted in the first or last bin, non-finite
+/// values are skipped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    min: f64,
+    max: f64,
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn new(bins: usize, min: f64, max: f64) -> Histogram {
+        assert!(bins > 0 && min < max); // TODO: real error handling
+
+        Histogram {
+            min,
+            max,
+            counts: vec![0; bins],
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+
+        let bins = self.counts.len();
+        let position = (value - self.min) / (self.max - self.min) * bins as f64;
+        let bin = (position.max(0.0) as usize).min(bins - 1);
+        self.counts[bin] += 1;
+    }
+
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Lower and upper bound of the given bin.
+    pub fn bin_range(&self, bin: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / self.counts.len() as f64;
+        (
+            self.min + bin as f64 * width,
+            self.min + (bin + 1) as f64 * width,
+        )
+    }
+}
+
+impl Canvas {
+    pub fn luminance_histogram(&self, bins: usize, min: f64, max: f64) -> Histogram {
+        let mut histogram = Histogram::new(bins, min, max);
+        for color in self.bitmap.iter() {
+            histogram.add(color.luminance());
+        }
+        histogram
+    }
+
+    /// Histograms of the red, green and blue channel.
+    pub fn channel_histograms(&self, bins: usize, min: f64, max: f64) -> [Histogram; 3] {
+        let mut histograms = [
+            Histogram::new(bins, min, max),
+            Histogram::new(bins, min, max),
+            Histogram::new(bins, min, max),
+        ];
+        for color in self.bitmap.iter() {
+            histograms[0].add(color.red);
+            histograms[1].add(color.green);
+            histograms[2].add(color.blue);
+        }
+        histograms
+    }
+
+    /// Per channel minimum, ignoring non-finite values. Channels without a
+    /// single finite value, e.g. of an empty canvas, are positive infinity.
+    pub fn min(&self) -> Color {
+        self.reduce(f64::INFINITY, f64::min)
+    }
+
+    /// Per channel maximum, ignoring non-finite values. Channels without a
+    /// single finite value are negative infinity.
+    pub fn max(&self) -> Color {
+        self.reduce(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Per channel mean, ignoring non-finite values. Channels without a
+    /// single finite value are zero.
+    pub fn mean(&self) -> Color {
+        let mut sum = [0.0; 3];
+        let mut count = [0usize; 3];
+        for color in self.bitmap.iter() {
+            for (channel, value) in [color.red, color.green, color.blue].iter().enumerate() {
+                if value.is_finite() {
+                    sum[channel] += value;
+                    count[channel] += 1;
+                }
+            }
+        }
+
+        let mean = |channel: usize| sum[channel] / count[channel].max(1) as f64;
+        Color::new(mean(0), mean(1), mean(2))
+    }
+
+    /// Coordinates of every pixel with a NaN in any channel.
+    pub fn nan_pixels(&self) -> Vec<(usize, usize)> {
+        self.find_pixels(|value| value.is_nan())
+    }
+
+    /// Coordinates of every pixel with an infinite value in any channel.
+    pub fn infinite_pixels(&self) -> Vec<(usize, usize)> {
+        self.find_pixels(|value| value.is_infinite())
+    }
+
+    /// Renders row-major scalar `values` as a false-color image, mapping the
+    /// smallest finite value to blue and the largest to red. Non-finite values
+    /// are marked in magenta.
+    pub fn heatmap(width: usize, height: usize, values: &[f64]) -> Canvas {
+        assert_eq!(values.len(), width * height); // TODO: real error handling
+
+        let finite = values.iter().filter(|value| value.is_finite());
+        let min = finite.clone().fold(f64::INFINITY, |a, &b| a.min(b));
+        let max = finite.fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+        let range = if max > min { max - min } else { 1.0 };
+        let ramp = ColorRamp::new(vec![
+            (0.0, Color::new(0.0, 0.0, 1.0)),
+            (0.25, Color::new(0.0, 1.0, 1.0)),
+            (0.5, Color::new(0.0, 1.0, 0.0)),
+            (0.75, Color::new(1.0, 1.0, 0.0)),
+            (1.0, Color::new(1.0, 0.0, 0.0)),
+        ]);
+
+        Canvas {
+            width,
+            height,
+            bitmap: values
+                .iter()
+                .map(|value| {
+                    if value.is_finite() {
+                        ramp.sample((value - min) / range)
+                    } else {
+                        Color::new(1.0, 0.0, 1.0)
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn reduce<F: Fn(f64, f64) -> f64>(&self, initial: f64, f: F) -> Color {
+        let channel = |value: f64, current: f64| {
+            if value.is_finite() {
+                f(current, value)
+            } else {
+                current
+            }
+        };
+
+        self.bitmap
+            .iter()
+            .fold(Color::new(initial, initial, initial), |result, color| {
+                Color::new(
+                    channel(color.red, result.red),
+                    channel(color.green, result.green),
+                    channel(color.blue, result.blue),
+                )
+            })
+    }
+
+    fn find_pixels<F: Fn(f64) -> bool>(&self, predicate: F) -> Vec<(usize, usize)> {
+        self.bitmap
+            .iter()
+            .enumerate()
+            .filter(|(_, color)| {
+                predicate(color.red) || predicate(color.green) || predicate(color.blue)
+            })
+            .map(|(index, _)| (index % self.width, index / self.width))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn binning_values() {
+        let mut histogram = Histogram::new(4, 0.0, 1.0);
+        for value in [-1.0, 0.1, 0.3, 0.3, 0.99, 1.0, 5.0, f64::NAN].iter() {
+            histogram.add(*value);
+        }
+
+        assert_eq!(histogram.counts(), &[2, 2, 0, 3]);
+        assert_eq!(histogram.total(), 7);
+        assert_eq!(histogram.bin_range(1), (0.25, 0.5));
+    }
+
+    #[test]
+    fn histograms_of_a_canvas() {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+        c.write_pixel(1, 0, Color::new(1.0, 0.0, 0.0));
+
+        assert_eq!(c.luminance_histogram(2, 0.0, 1.0).counts(), &[3, 1]);
+        let [red, green, _] = c.channel_histograms(2, 0.0, 1.0);
+        assert_eq!(red.counts(), &[2, 2]);
+        assert_eq!(green.counts(), &[3, 1]);
+    }
+
+    #[test]
+    fn statistics_skip_non_finite_values() {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(0, 0, Color::new(1.0, -2.0, 0.5));
+        c.write_pixel(1, 0, Color::new(3.0, f64::NAN, 0.5));
+        c.write_pixel(0, 1, Color::new(f64::INFINITY, 0.0, 0.5));
+
+        assert_eq!(c.min(), Color::new(0.0, -2.0, 0.0));
+        assert_eq!(c.max(), Color::new(3.0, 0.0, 0.5));
+        assert_eq!(c.mean(), Color::new(4.0 / 3.0, -2.0 / 3.0, 0.375));
+
+        let mut broken = Canvas::new(1, 1);
+        broken.write_pixel(0, 0, Color::new(f64::NAN, 1.0, f64::INFINITY));
+        assert_eq!(broken.min().red, f64::INFINITY);
+        assert_eq!(broken.max().blue, f64::NEG_INFINITY);
+        assert_eq!(broken.mean().red, 0.0);
+    }
+
+    #[test]
+    fn finding_nan_and_infinite_pixels() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(2, 0, Color::new(0.0, f64::NAN, 0.0));
+        c.write_pixel(1, 1, Color::new(f64::NEG_INFINITY, 0.0, f64::NAN));
+
+        assert_eq!(c.nan_pixels(), vec![(2, 0), (1, 1)]);
+        assert_eq!(c.infinite_pixels(), vec![(1, 1)]);
+        assert!(Canvas::new(3, 2).nan_pixels().is_empty());
+    }
+
+    #[test]
+    fn rendering_a_heatmap() {
+        let c = Canvas::heatmap(2, 2, &[0.0, 5.0, 10.0, f64::NAN]);
+
+        assert_eq!(c.pixel_at(0, 0), Color::new(0.0, 0.0, 1.0));
+        assert_eq!(c.pixel_at(1, 0), Color::new(0.0, 1.0, 0.0));
+        assert_eq!(c.pixel_at(0, 1), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(c.pixel_at(1, 1), Color::new(1.0, 0.0, 1.0));
+    }
+}
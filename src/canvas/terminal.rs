@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use crate::canvas::quantize::{bayer_threshold, Quantization};
+use crate::canvas::resample::ResizeFilter;
+use crate::canvas::Canvas;
+use crate::color::TransferFunction;
+
+// sixel palettes are a 6x6x6 color cube
+const LEVELS: usize = 6;
+
+impl Canvas {
+    /// Renders the canvas with 24-bit ANSI colors, two pixels per character
+    /// using the upper half block. Canvases wider than `columns` are
+    /// downscaled to fit.
+    pub fn to_ansi(&self, columns: usize) -> String {
+        let fitted = self.fit_width(columns);
+        let pixels = fitted.quantize(TransferFunction::Srgb, Quantization::Round);
+        let width = fitted.width;
+        let mut output = String::new();
+
+        for y in (0..fitted.height).step_by(2) {
+            for x in 0..width {
+                let [r, g, b] = pixels[y * width + x];
+                write!(output, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+                match pixels.get((y + 1) * width + x) {
+                    Some([r, g, b]) => write!(output, "\x1b[48;2;{};{};{}m", r, g, b).unwrap(),
+                    None => output.push_str("\x1b[49m"),
+                }
+                output.push('\u{2580}');
+            }
+            output.push_str("\x1b[0m\n");
+        }
+
+        output
+    }
+
+    /// Renders the canvas as a sixel image with a 216 color palette, at most
+    /// `max_width` pixels wide.
+    pub fn to_sixel(&self, max_width: usize) -> String {
+        let fitted = self.fit_width(max_width);
+        // dither straight to the six palette levels, one step per threshold
+        let indices: Vec<usize> = fitted
+            .enumerate_pixels()
+            .map(|(x, y, color)| {
+                let color = TransferFunction::Srgb.encode_color(*color);
+                let offset = bayer_threshold(x, y);
+                let level = |c: f64| {
+                    let steps = (LEVELS - 1) as f64;
+                    (c * steps + offset).floor().clamp(0.0, steps) as usize
+                };
+                (level(color.red) * LEVELS + level(color.green)) * LEVELS + level(color.blue)
+            })
+            .collect();
+        let (width, height) = (fitted.width, fitted.height);
+
+        let mut output = format!("\x1bPq\"1;1;{};{}", width, height);
+        for index in 0..LEVELS * LEVELS * LEVELS {
+            let percent = |level: usize| level * 100 / (LEVELS - 1);
+            let (r, g, b) = (
+                index / (LEVELS * LEVELS),
+                index / LEVELS % LEVELS,
+                index % LEVELS,
+            );
+            write!(
+                output,
+                "#{};2;{};{};{}",
+                index,
+                percent(r),
+                percent(g),
+                percent(b)
+            )
+            .unwrap();
+        }
+
+        // each band covers six rows, one bit per row
+        let mut used = vec![false; LEVELS * LEVELS * LEVELS];
+        for top in (0..height).step_by(6) {
+            if top > 0 {
+                output.push('-');
+            }
+            let rows = top..(top + 6).min(height);
+
+            used.iter_mut().for_each(|u| *u = false);
+            for y in rows.clone() {
+                for &index in &indices[y * width..(y + 1) * width] {
+                    used[index] = true;
+                }
+            }
+
+            for color in (0..used.len()).filter(|&color| used[color]) {
+                write!(output, "#{}", color).unwrap();
+                let mut run: Option<(u8, usize)> = None;
+                for x in 0..width {
+                    let bits = rows
+                        .clone()
+                        .filter(|y| indices[y * width + x] == color)
+                        .fold(0, |bits, y| bits | 1 << (y - top));
+                    let character = 63 + bits;
+                    run = match run {
+                        Some((c, count)) if c == character => Some((c, count + 1)),
+                        Some((c, count)) => {
+                            push_run(&mut output, c, count);
+                            Some((character, 1))
+                        }
+                        None => Some((character, 1)),
+                    };
+                }
+                if let Some((c, count)) = run {
+                    push_run(&mut output, c, count);
+                }
+                output.push('$');
+            }
+        }
+
+        output.push_str("\x1b\\");
+        output
+    }
+
+    fn fit_width(&self, max_width: usize) -> Canvas {
+        if self.width <= max_width {
+            return self.crop(0, 0, self.width, self.height);
+        }
+
+        let height = (self.height * max_width + self.width / 2) / self.width;
+        self.resize(max_width, height.max(1), ResizeFilter::Box)
+    }
+}
+
+fn push_run(output: &mut String, character: u8, count: usize) {
+    if count > 3 {
+        write!(output, "!{}{}", count, character as char).unwrap();
+    } else {
+        for _ in 0..count {
+            output.push(character as char);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn rendering_half_blocks() {
+        let mut c = Canvas::new(2, 3);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        c.write_pixel(1, 1, Color::new(0.0, 0.0, 1.0));
+        c.write_pixel(0, 2, Color::new(1.0, 1.0, 1.0));
+
+        assert_eq!(
+            c.to_ansi(80),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m\u{2580}\
+             \x1b[38;2;0;0;0m\x1b[48;2;0;0;255m\u{2580}\x1b[0m\n\
+             \x1b[38;2;255;255;255m\x1b[49m\u{2580}\
+             \x1b[38;2;0;0;0m\x1b[49m\u{2580}\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn downscaling_to_the_terminal_width() {
+        let c = Canvas::new(40, 20);
+        let preview = c.to_ansi(10);
+
+        assert_eq!(preview.lines().count(), 3);
+        assert_eq!(
+            preview.lines().next().unwrap().matches('\u{2580}').count(),
+            10
+        );
+    }
+
+    #[test]
+    fn encoding_sixels() {
+        let mut c = Canvas::new(5, 7);
+        c.fill_rectangle(0, 0, 5, 7, Color::new(1.0, 0.0, 0.0));
+        c.write_pixel(0, 6, Color::new(0.0, 0.0, 1.0));
+        let sixel = c.to_sixel(100);
+
+        assert!(sixel.starts_with("\x1bPq\"1;1;5;7#0;2;0;0;0#1;2;0;0;20"));
+        assert!(sixel.contains("#180;2;100;0;0"));
+        assert!(sixel.ends_with("#180!5~$-#5@!4?$#180?!4@$\x1b\\"));
+    }
+
+    #[test]
+    fn dithering_sixels_between_palette_levels() {
+        // halfway between the encoded levels 2/5 and 3/5
+        let gray = TransferFunction::Srgb.decode(0.5);
+        let mut c = Canvas::new(8, 8);
+        c.fill_rectangle(0, 0, 8, 8, Color::new(gray, gray, gray));
+        let sixel = c.to_sixel(100);
+        let (_, bands) = sixel.split_at(sixel.find("#215;2;100;100;100").unwrap());
+
+        // a checkerboard of the two neighbouring grays, nothing else
+        assert_eq!(
+            bands,
+            "#215;2;100;100;100#86TiTiTiTi$#129iTiTiTiT$-#86@A@A@A@A$#129A@A@A@A@$\x1b\\"
+        );
+    }
+}
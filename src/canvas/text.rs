@@ -0,0 +1,250 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+const ADVANCE: usize = GLYPH_WIDTH + 1;
+const LINE_HEIGHT: usize = GLYPH_HEIGHT + 2;
+const FIRST_CHAR: char = ' ';
+const FALLBACK_CHAR: char = '?';
+
+// classic 5x7 font for printable ASCII, one byte per column with bit 0 at the top
+#[rustfmt::skip]
+const FONT: [[u8; GLYPH_WIDTH]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // '#'
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x56, 0x20, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '''
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // ')'
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // '*'
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // '0'
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // '1'
+    [0x42, 0x61, 0x51, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // '3'
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // '6'
+    [0x01, 0x71, 0x09, 0x05, 0x03], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // '@'
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // 'A'
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // 'D'
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // 'F'
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // 'G'
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // 'H'
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // 'J'
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // 'M'
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // 'N'
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // 'O'
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // 'Q'
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x46, 0x49, 0x49, 0x49, 0x31], // 'S'
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // 'T'
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // 'U'
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // 'V'
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x07, 0x08, 0x70, 0x08, 0x07], // 'Y'
+    [0x61, 0x51, 0x49, 0x45, 0x43], // 'Z'
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\'
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x20], // 'c'
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // 'f'
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // 'g'
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // 'j'
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // 'k'
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // 'l'
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // 'm'
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // 'q'
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x20], // 's'
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // 't'
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // 'u'
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // 'v'
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // 'y'
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x02, 0x01, 0x02, 0x04, 0x02], // '~'
+];
+
+impl Canvas {
+    /// Size in pixels of `text` drawn at `scale`, lines are separated by `\n`.
+    pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+        let lines: Vec<&str> = text.split('\n').collect();
+        let columns = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let width = if columns == 0 {
+            0
+        } else {
+            (columns * ADVANCE - 1) * scale
+        };
+        let height = (lines.len() * LINE_HEIGHT - (LINE_HEIGHT - GLYPH_HEIGHT)) * scale;
+        (width, height)
+    }
+
+    /// Draws `text` with its top left corner at `(x, y)`, each font pixel
+    /// becoming a `scale` by `scale` block. Characters outside printable
+    /// ASCII are drawn as `?`.
+    pub fn draw_text(&mut self, x: isize, y: isize, text: &str, color: Color, scale: usize) {
+        let scale_offset = scale as isize;
+
+        for (row, line) in text.split('\n').enumerate() {
+            let top = y + (row * LINE_HEIGHT) as isize * scale_offset;
+
+            for (column, character) in line.chars().enumerate() {
+                let left = x + (column * ADVANCE) as isize * scale_offset;
+                let glyph = glyph(character);
+
+                for (gx, bits) in glyph.iter().enumerate() {
+                    for gy in 0..GLYPH_HEIGHT {
+                        if bits & (1 << gy) != 0 {
+                            self.fill_rectangle(
+                                left + gx as isize * scale_offset,
+                                top + gy as isize * scale_offset,
+                                scale,
+                                scale,
+                                color,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws `text` on a filled box extending one font pixel around it, so it
+    /// stays readable on top of a render.
+    pub fn draw_label(
+        &mut self,
+        x: isize,
+        y: isize,
+        text: &str,
+        color: Color,
+        background: Color,
+        scale: usize,
+    ) {
+        let padding = scale;
+        let (width, height) = Canvas::text_size(text, scale);
+
+        self.fill_rectangle(x, y, width + 2 * padding, height + 2 * padding, background);
+        self.draw_text(
+            x + padding as isize,
+            y + padding as isize,
+            text,
+            color,
+            scale,
+        );
+    }
+}
+
+fn glyph(character: char) -> &'static [u8; GLYPH_WIDTH] {
+    let index = character as usize;
+    let first = FIRST_CHAR as usize;
+
+    if index >= first && index - first < FONT.len() {
+        &FONT[index - first]
+    } else {
+        &FONT[FALLBACK_CHAR as usize - first]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measuring_text() {
+        assert_eq!(Canvas::text_size("", 1), (0, 7));
+        assert_eq!(Canvas::text_size("A", 1), (5, 7));
+        assert_eq!(Canvas::text_size("frame 12", 2), (94, 14));
+        assert_eq!(Canvas::text_size("ab\nc", 1), (11, 16));
+    }
+
+    #[test]
+    fn drawing_a_character() {
+        let mut c = Canvas::new(8, 8);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_text(1, 1, "L", white, 1);
+
+        for y in 1..8 {
+            assert_eq!(c.pixel_at(1, y), white);
+        }
+        assert_eq!(c.pixel_at(5, 7), white);
+        assert_eq!(c.pixel_at(5, 6), Color::black());
+        assert_eq!(c.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn drawing_scaled_text_is_clipped() {
+        let mut c = Canvas::new(4, 4);
+        let white = Color::new(1.0, 1.0, 1.0);
+        c.draw_text(-2, 0, "|", white, 2);
+
+        assert_eq!(c.pixel_at(2, 0), white);
+        assert_eq!(c.pixel_at(3, 3), white);
+        assert_eq!(c.pixel_at(1, 0), Color::black());
+    }
+
+    #[test]
+    fn unknown_characters_use_the_fallback_glyph() {
+        assert_eq!(glyph('é'), glyph('?'));
+        assert_eq!(glyph('~'), &FONT[94]);
+    }
+
+    #[test]
+    fn drawing_a_label() {
+        let mut c = Canvas::new(10, 10);
+        let grey = Color::new(0.5, 0.5, 0.5);
+        c.draw_label(0, 0, "-", Color::new(1.0, 1.0, 1.0), grey, 1);
+
+        assert_eq!(c.pixel_at(0, 0), grey);
+        assert_eq!(c.pixel_at(6, 8), grey);
+        assert_eq!(c.pixel_at(1, 4), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(7, 0), Color::black());
+    }
+}
@@ -0,0 +1,239 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::canvas::{Canvas, Pixel};
+use crate::canvas::Quantization;
+use crate::color::{Color, TransferFunction};
+
+// see: http://www.gamers.org/dEngine/quake3/TGA.txt
+const HEADER_LENGTH: usize = 18;
+const UNCOMPRESSED_RGB: u8 = 2;
+const RLE_RGB: u8 = 10;
+const TOP_LEFT_ORIGIN: u8 = 0b0010_0000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TgaError {
+    InvalidHeader,
+    UnsupportedImageType,
+    UnsupportedPixelDepth,
+    UnexpectedEof,
+}
+
+impl fmt::Display for TgaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            TgaError::InvalidHeader => "invalid targa header",
+            TgaError::UnsupportedImageType => "unsupported image type, expected true color",
+            TgaError::UnsupportedPixelDepth => "unsupported pixel depth, expected 24 or 32 bits",
+            TgaError::UnexpectedEof => "unexpected end of file",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl Error for TgaError {}
+
+impl<P: Pixel> Canvas<P> {
+    /// Writes the canvas display encoded as sRGB.
+    pub fn to_tga(&self) -> Vec<u8> {
+        self.to_tga_encoded(TransferFunction::Srgb, Quantization::Round)
+    }
+
+    pub fn to_tga_encoded(
+        &self,
+        transfer: TransferFunction,
+        quantization: Quantization,
+    ) -> Vec<u8> {
+        let mut tga: [u8; HEADER_LENGTH] = [0; HEADER_LENGTH];
+
+        // tga header
+        tga[2] = UNCOMPRESSED_RGB;
+        tga[12] = (255 & self.width) as u8;
+        tga[13] = (255 & (self.width >> 8)) as u8;
+        tga[14] = (255 & self.height) as u8;
+        tga[15] = (255 & (self.height >> 8)) as u8;
+        tga[16] = 24; // 24 bits per pixel
+        tga[17] = TOP_LEFT_ORIGIN; // screen origin upper left-hand corner
+
+        let mut image: Vec<u8> = tga.to_vec();
+
+        for [red, green, blue] in self.quantize(transfer, quantization) {
+            image.push(blue);
+            image.push(green);
+            image.push(red);
+        }
+
+        image
+    }
+}
+
+impl Canvas {
+    /// Reads a true color targa image and linearizes it from sRGB.
+    pub fn from_tga(data: &[u8]) -> Result<Canvas, TgaError> {
+        Canvas::from_tga_decoded(data, TransferFunction::Srgb)
+    }
+
+    pub fn from_tga_decoded(data: &[u8], transfer: TransferFunction) -> Result<Canvas, TgaError> {
+        if data.len() < HEADER_LENGTH {
+            return Err(TgaError::InvalidHeader);
+        }
+        if data[1] != 0 {
+            return Err(TgaError::UnsupportedImageType);
+        }
+
+        let image_type = data[2];
+        if image_type != UNCOMPRESSED_RGB && image_type != RLE_RGB {
+            return Err(TgaError::UnsupportedImageType);
+        }
+
+        let bytes_per_pixel = match data[16] {
+            24 => 3,
+            32 => 4,
+            _ => return Err(TgaError::UnsupportedPixelDepth),
+        };
+
+        let width = data[12] as usize | (data[13] as usize) << 8;
+        let height = data[14] as usize | (data[15] as usize) << 8;
+        let top_to_bottom = data[17] & TOP_LEFT_ORIGIN != 0;
+        let start = HEADER_LENGTH + data[0] as usize;
+
+        let pixels = if image_type == RLE_RGB {
+            decode_rle(
+                data.get(start..).ok_or(TgaError::UnexpectedEof)?,
+                width * height,
+                bytes_per_pixel,
+            )?
+        } else {
+            let end = start + width * height * bytes_per_pixel;
+            data.get(start..end)
+                .ok_or(TgaError::UnexpectedEof)?
+                .to_vec()
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        for (index, pixel) in pixels.chunks(bytes_per_pixel).enumerate() {
+            let x = index % width;
+            let row = index / width;
+            let y = if top_to_bottom { row } else { height - 1 - row };
+
+            let color = Color::new(
+                pixel[2] as f64 / 255.0,
+                pixel[1] as f64 / 255.0,
+                pixel[0] as f64 / 255.0,
+            );
+            canvas.write_pixel(x, y, transfer.decode_color(color));
+        }
+
+        Ok(canvas)
+    }
+}
+
+fn decode_rle(
+    data: &[u8],
+    pixel_count: usize,
+    bytes_per_pixel: usize,
+) -> Result<Vec<u8>, TgaError> {
+    let mut pixels: Vec<u8> = Vec::with_capacity(pixel_count * bytes_per_pixel);
+    let mut position = 0;
+
+    while pixels.len() < pixel_count * bytes_per_pixel {
+        let packet = *data.get(position).ok_or(TgaError::UnexpectedEof)?;
+        let count = (packet & 0x7f) as usize + 1;
+        position += 1;
+
+        if packet & 0x80 != 0 {
+            let pixel = data
+                .get(position..position + bytes_per_pixel)
+                .ok_or(TgaError::UnexpectedEof)?;
+            for _ in 0..count {
+                pixels.extend_from_slice(pixel);
+            }
+            position += bytes_per_pixel;
+        } else {
+            let length = count * bytes_per_pixel;
+            let raw = data
+                .get(position..position + length)
+                .ok_or(TgaError::UnexpectedEof)?;
+            pixels.extend_from_slice(raw);
+            position += length;
+        }
+    }
+
+    pixels.truncate(pixel_count * bytes_per_pixel);
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writing_display_encoded_tga() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.2158, 0.0));
+        let tga = c.to_tga();
+
+        assert_eq!(tga.len(), 18 + 2 * 3);
+        assert_eq!(tga[16], 24);
+        assert_eq!(&tga[18..21], &[0, 128, 255]);
+
+        let linear = c.to_tga_encoded(TransferFunction::Linear, Quantization::Round);
+        assert_eq!(&linear[18..21], &[0, 55, 255]);
+    }
+
+    #[test]
+    fn tga_round_trip() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(2, 1, Color::new(0.5, 0.25, 1.0));
+
+        let decoded = Canvas::from_tga(&c.to_tga()).unwrap();
+        let pixel = decoded.pixel_at(2, 1);
+        assert_eq!(decoded.width(), 3);
+        assert!((pixel.red - 0.5).abs() < 0.01);
+        assert!((pixel.green - 0.25).abs() < 0.01);
+        assert_eq!(pixel.blue, 1.0);
+        assert_eq!(decoded.pixel_at(0, 0), Color::black());
+    }
+
+    #[test]
+    fn reading_bottom_up_run_length_encoded_tga() {
+        let mut tga = vec![0; 18];
+        tga[2] = RLE_RGB;
+        tga[12] = 2;
+        tga[14] = 2;
+        tga[16] = 32;
+        // a run of two white pixels, then two raw pixels
+        tga.extend_from_slice(&[
+            0x81, 255, 255, 255, 255, 0x01, 0, 0, 255, 255, 0, 255, 0, 255,
+        ]);
+
+        let c = Canvas::from_tga_decoded(&tga, TransferFunction::Linear).unwrap();
+        assert_eq!(c.pixel_at(0, 1), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(c.pixel_at(1, 0), Color::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn reading_invalid_tga_data() {
+        let mut tga = Canvas::new(2, 2).to_tga();
+
+        assert_eq!(
+            Canvas::from_tga(&tga[..10]).err(),
+            Some(TgaError::InvalidHeader)
+        );
+        assert_eq!(
+            Canvas::from_tga(&tga[..20]).err(),
+            Some(TgaError::UnexpectedEof)
+        );
+        tga[16] = 16;
+        assert_eq!(
+            Canvas::from_tga(&tga).err(),
+            Some(TgaError::UnsupportedPixelDepth)
+        );
+        tga[2] = 1;
+        assert_eq!(
+            Canvas::from_tga(&tga).err(),
+            Some(TgaError::UnsupportedImageType)
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+const LOG_AVERAGE_DELTA: f64 = 1.0e-4;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Exposure {
+    /// Scales by `2^stops`.
+    Fixed(f64),
+    /// Maps the log-average luminance of the canvas to `key`, 0.18 is middle grey.
+    Auto { key: f64 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ToneMapOperator {
+    /// Leaves colors untouched, `Color::convert_component` clips them on export.
+    Clamp,
+    Reinhard,
+    /// Reinhard with a luminance that maps to pure white.
+    ReinhardExtended { white_point: f64 },
+    /// John Hable's Uncharted 2 filmic curve, 11.2 is the usual white point.
+    Hable { white_point: f64 },
+    /// Stephen Hill's fit of the ACES reference rendering and output transforms.
+    Aces,
+}
+
+impl ToneMapOperator {
+    pub fn map(&self, color: Color) -> Color {
+        match *self {
+            ToneMapOperator::Clamp => color,
+            ToneMapOperator::Reinhard => scale_luminance(color, |l| l / (1.0 + l)),
+            ToneMapOperator::ReinhardExtended { white_point } => {
+                let white_squared = white_point * white_point;
+                scale_luminance(color, |l| l * (1.0 + l / white_squared) / (1.0 + l))
+            }
+            ToneMapOperator::Hable { white_point } => {
+                let white_scale = 1.0 / hable(white_point);
+                Color::new(
+                    hable(color.red * 2.0) * white_scale,
+                    hable(color.green * 2.0) * white_scale,
+                    hable(color.blue * 2.0) * white_scale,
+                )
+            }
+            ToneMapOperator::Aces => aces(color),
+        }
+    }
+}
+
+impl Canvas {
+    pub fn log_average_luminance(&self) -> f64 {
+        let mut sum = 0.0;
+        let mut count = 0;
+
+        for color in &self.bitmap {
+            let l = color.luminance();
+            if l.is_finite() {
+                sum += (LOG_AVERAGE_DELTA + l.max(0.0)).ln();
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            return 0.0;
+        }
+        (sum / count as f64).exp()
+    }
+
+    pub fn apply_exposure(&self, exposure: Exposure) -> Canvas {
+        let scale = match exposure {
+            Exposure::Fixed(stops) => 2.0_f64.powf(stops),
+            Exposure::Auto { key } => {
+                let average = self.log_average_luminance();
+                if average > 0.0 {
+                    key / average
+                } else {
+                    1.0
+                }
+            }
+        };
+
+        self.map_colors(|color| color * scale)
+    }
+
+    pub fn tone_map(&self, operator: ToneMapOperator) -> Canvas {
+        self.map_colors(|color| operator.map(color))
+    }
+}
+
+fn scale_luminance<F: Fn(f64) -> f64>(color: Color, curve: F) -> Color {
+    let l = color.luminance();
+    if l <= 0.0 {
+        return Color::black();
+    }
+    color * (curve(l) / l)
+}
+
+fn hable(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+fn aces(color: Color) -> Color {
+    let input = [
+        [0.59719, 0.35458, 0.04823],
+        [0.07600, 0.90834, 0.01566],
+        [0.02840, 0.13383, 0.83777],
+    ];
+    let output = [
+        [1.60475, -0.53108, -0.07367],
+        [-0.10208, 1.10813, -0.00605],
+        [-0.00327, -0.07276, 1.07602],
+    ];
+
+    let fit = |v: f64| {
+        let a = v * (v + 0.0245786) - 0.000090537;
+        let b = v * (0.983729 * v + 0.4329510) + 0.238081;
+        a / b
+    };
+
+    let v = multiply(input, color);
+    let v = multiply(output, Color::new(fit(v.red), fit(v.green), fit(v.blue)));
+    v.clamp(0.0, 1.0)
+}
+
+fn multiply(m: [[f64; 3]; 3], c: Color) -> Color {
+    Color::new(
+        m[0][0] * c.red + m[0][1] * c.green + m[0][2] * c.blue,
+        m[1][0] * c.red + m[1][1] * c.green + m[1][2] * c.blue,
+        m[2][0] * c.red + m[2][1] * c.green + m[2][2] * c.blue,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reinhard_compresses_highlights_below_one() {
+        let mapped = ToneMapOperator::Reinhard.map(Color::new(10.0, 10.0, 10.0));
+
+        assert_eq!(mapped, Color::new(10.0 / 11.0, 10.0 / 11.0, 10.0 / 11.0));
+        assert_eq!(ToneMapOperator::Reinhard.map(Color::black()), Color::black());
+    }
+
+    #[test]
+    fn extended_reinhard_maps_white_point_to_one() {
+        let operator = ToneMapOperator::ReinhardExtended { white_point: 4.0 };
+
+        assert_eq!(operator.map(Color::new(4.0, 4.0, 4.0)), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn hable_maps_white_point_to_one() {
+        let operator = ToneMapOperator::Hable { white_point: 11.2 };
+        let white = operator.map(Color::new(5.6, 5.6, 5.6));
+
+        assert!((white.red - 1.0).abs() < 1.0e-6);
+        assert!(operator.map(Color::black()).green.abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn aces_stays_in_display_range() {
+        let bright = ToneMapOperator::Aces.map(Color::new(100.0, 50.0, 1.0));
+        let grey = ToneMapOperator::Aces.map(Color::new(0.18, 0.18, 0.18));
+
+        assert!(bright.red <= 1.0 && bright.green <= 1.0 && bright.blue >= 0.0);
+        assert!((grey.red - 0.1056).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn auto_exposure_maps_average_to_key() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+        c.write_pixel(1, 0, Color::new(4.0, 4.0, 4.0));
+
+        assert!((c.log_average_luminance() - 2.0).abs() < 1.0e-3);
+
+        let exposed = c.apply_exposure(Exposure::Auto { key: 0.18 });
+        assert_eq!(exposed.pixel_at(0, 0), Color::new(0.09, 0.09, 0.09));
+
+        let exposed = c.apply_exposure(Exposure::Fixed(-2.0));
+        assert_eq!(exposed.pixel_at(1, 0), Color::new(1.0, 1.0, 1.0));
+    }
+}
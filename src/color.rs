@@ -1,5 +1,23 @@
+mod blackbody;
+mod conversion;
+mod ramp;
+mod spectrum;
+mod transfer;
+mod working_space;
+
+use std::iter;
 use std::ops;
 
+pub use self::blackbody::BlackbodyNormalization;
+pub use self::conversion::ParseColorError;
+pub(crate) use self::conversion::D65;
+pub use self::ramp::{ColorRamp, RampMode, RampSpace};
+pub use self::spectrum::{
+    HeroWavelengths, Spectrum, HERO_WAVELENGTHS, MAX_WAVELENGTH, MIN_WAVELENGTH,
+};
+pub use self::transfer::TransferFunction;
+pub use self::working_space::{ColorSpace, WhitePoint};
+
 const EPSILON: f64 = 10e-6;
 
 #[derive(Copy, Clone, Debug)]
@@ -18,6 +36,36 @@ impl Color {
         Color::new(0.0, 0.0, 0.0)
     }
 
+    /// Rec. 709 relative luminance, assuming linear values.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
+    /// Linear interpolation, giving `self` at 0 and `other` at 1.
+    pub fn lerp(&self, other: Color, t: f64) -> Color {
+        *self + (other - *self) * t
+    }
+
+    pub fn clamp(&self, min: f64, max: f64) -> Color {
+        Color::new(
+            self.red.clamp(min, max),
+            self.green.clamp(min, max),
+            self.blue.clamp(min, max),
+        )
+    }
+
+    /// Largest of the three components, NaN if any of them is NaN.
+    pub fn max_component(&self) -> f64 {
+        if self.red.is_nan() || self.green.is_nan() || self.blue.is_nan() {
+            return f64::NAN;
+        }
+        self.red.max(self.green).max(self.blue)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.red.is_finite() && self.green.is_finite() && self.blue.is_finite()
+    }
+
     pub fn convert_component(component: f64) -> u8 {
         let value = (component * 255.0).round() as i32;
         match value {
@@ -72,6 +120,84 @@ impl ops::Mul<Color> for Color {
     }
 }
 
+impl ops::Mul<Color> for f64 {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Self::Output {
+        other * self
+    }
+}
+
+impl ops::Div<f64> for Color {
+    type Output = Self;
+
+    fn div(self, other: f64) -> Self::Output {
+        Color::new(self.red / other, self.green / other, self.blue / other)
+    }
+}
+
+impl ops::Div<Color> for Color {
+    type Output = Self;
+
+    fn div(self, other: Color) -> Self::Output {
+        Color::new(
+            self.red / other.red,
+            self.green / other.green,
+            self.blue / other.blue,
+        )
+    }
+}
+
+impl ops::Neg for Color {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Color::new(-self.red, -self.green, -self.blue)
+    }
+}
+
+impl ops::AddAssign<Color> for Color {
+    fn add_assign(&mut self, other: Color) {
+        *self = *self + other;
+    }
+}
+
+impl ops::SubAssign<Color> for Color {
+    fn sub_assign(&mut self, other: Color) {
+        *self = *self - other;
+    }
+}
+
+impl ops::MulAssign<f64> for Color {
+    fn mul_assign(&mut self, other: f64) {
+        *self = *self * other;
+    }
+}
+
+impl ops::MulAssign<Color> for Color {
+    fn mul_assign(&mut self, other: Color) {
+        *self = *self * other;
+    }
+}
+
+impl ops::DivAssign<f64> for Color {
+    fn div_assign(&mut self, other: f64) {
+        *self = *self / other;
+    }
+}
+
+impl iter::Sum for Color {
+    fn sum<I: Iterator<Item = Color>>(iter: I) -> Color {
+        iter.fold(Color::black(), |sum, color| sum + color)
+    }
+}
+
+impl<'a> iter::Sum<&'a Color> for Color {
+    fn sum<I: Iterator<Item = &'a Color>>(iter: I) -> Color {
+        iter.fold(Color::black(), |sum, color| sum + *color)
+    }
+}
+
 impl PartialEq for Color {
     fn eq(&self, other: &Color) -> bool {
         (self.red - other.red).abs() <= EPSILON
@@ -114,4 +240,53 @@ mod test {
 
         assert_eq!(c1 * c2, Color::new(0.9, 0.2, 0.04))
     }
+
+    #[test]
+    fn scaling_colors() {
+        let c = Color::new(0.2, 0.4, 0.8);
+
+        assert_eq!(2.0 * c, Color::new(0.4, 0.8, 1.6));
+        assert_eq!(c / 2.0, Color::new(0.1, 0.2, 0.4));
+        assert_eq!(c / Color::new(0.2, 0.2, 0.4), Color::new(1.0, 2.0, 2.0));
+        assert_eq!(-c, Color::new(-0.2, -0.4, -0.8));
+    }
+
+    #[test]
+    fn assigning_operators() {
+        let mut c = Color::new(0.2, 0.4, 0.8);
+        c += Color::new(0.1, 0.1, 0.1);
+        c -= Color::new(0.1, 0.2, 0.3);
+        c *= 2.0;
+        c *= Color::new(1.0, 0.5, 0.0);
+        c /= 4.0;
+
+        assert_eq!(c, Color::new(0.1, 0.075, 0.0));
+    }
+
+    #[test]
+    fn summing_colors() {
+        let samples = vec![Color::new(0.1, 0.2, 0.3), Color::new(0.3, 0.2, 0.1)];
+
+        assert_eq!(samples.iter().sum::<Color>(), Color::new(0.4, 0.4, 0.4));
+        assert_eq!(
+            samples.into_iter().sum::<Color>() / 2.0,
+            Color::new(0.2, 0.2, 0.2)
+        );
+        assert_eq!(Vec::<Color>::new().iter().sum::<Color>(), Color::black());
+    }
+
+    #[test]
+    fn color_utilities() {
+        let c = Color::new(-0.5, 0.5, 1.5);
+
+        assert_eq!(Color::new(1.0, 1.0, 1.0).luminance(), 1.0);
+        assert_eq!(Color::new(0.0, 1.0, 0.0).luminance(), 0.7152);
+        assert_eq!(c.clamp(0.0, 1.0), Color::new(0.0, 0.5, 1.0));
+        assert_eq!(c.max_component(), 1.5);
+        assert!(Color::new(f64::NAN, 2.0, 0.0).max_component().is_nan());
+        assert_eq!(Color::black().lerp(c, 0.5), Color::new(-0.25, 0.25, 0.75));
+        assert!(c.is_finite());
+        assert!(!Color::new(0.0, f64::NAN, 0.0).is_finite());
+        assert!(!Color::new(f64::INFINITY, 0.0, 0.0).is_finite());
+    }
 }
@@ -0,0 +1,165 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+const PLANCK: f64 = 6.626_070_15e-34;
+const BOLTZMANN: f64 = 1.380_649e-23;
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+// visible range covered by the color matching functions, in nanometers
+const FIRST_WAVELENGTH: usize = 360;
+const LAST_WAVELENGTH: usize = 830;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlackbodyNormalization {
+    /// Scales to a luminance of one.
+    Luminance,
+    /// Scales the largest component to one.
+    MaxComponent,
+}
+
+impl Color {
+    /// Color of a blackbody radiator at `temperature` Kelvin, with a
+    /// luminance of one.
+    pub fn from_kelvin(temperature: f64) -> Color {
+        Color::from_kelvin_normalized(temperature, BlackbodyNormalization::Luminance)
+    }
+
+    /// Integrates Planck's law against the CIE 1931 color matching functions.
+    /// Components outside the sRGB gamut are clipped to zero before
+    /// normalizing.
+    pub fn from_kelvin_normalized(
+        temperature: f64,
+        normalization: BlackbodyNormalization,
+    ) -> Color {
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        for wavelength in FIRST_WAVELENGTH..=LAST_WAVELENGTH {
+            let wavelength = wavelength as f64;
+            let radiance = planck(wavelength, temperature);
+            let (cx, cy, cz) = color_matching(wavelength);
+            x += radiance * cx;
+            y += radiance * cy;
+            z += radiance * cz;
+        }
+
+        let color = Color::from_xyz(x, y, z);
+        let color = color.clamp(0.0, f64::INFINITY);
+
+        let scale = match normalization {
+            BlackbodyNormalization::Luminance => color.to_xyz().1,
+            BlackbodyNormalization::MaxComponent => color.max_component(),
+        };
+        if scale > 0.0 {
+            color / scale
+        } else {
+            color
+        }
+    }
+}
+
+impl Canvas {
+    /// Rebalances a canvas lit by a blackbody at `source` Kelvin so that it
+    /// appears lit at `target` Kelvin, scaling each channel by the ratio of
+    /// the two light colors. Both lights lie on the Planckian locus, so a
+    /// 6504 K target is close to but not exactly D65, which sits on the
+    /// daylight locus slightly towards green.
+    pub fn white_balance(&self, source: f64, target: f64) -> Canvas {
+        let from = Color::from_kelvin(source);
+        let to = Color::from_kelvin(target);
+        let gain = |to: f64, from: f64| if from > 0.0 { to / from } else { 1.0 };
+        let gains = Color::new(
+            gain(to.red, from.red),
+            gain(to.green, from.green),
+            gain(to.blue, from.blue),
+        );
+
+        self.map_colors(|color| color * gains)
+    }
+}
+
+/// Spectral radiance at `wavelength` nanometers, in W / (sr m^2 nm).
+pub(crate) fn planck(wavelength: f64, temperature: f64) -> f64 {
+    let meters = wavelength * 1.0e-9;
+    let numerator = 2.0 * PLANCK * SPEED_OF_LIGHT * SPEED_OF_LIGHT / meters.powi(5);
+    let exponent = PLANCK * SPEED_OF_LIGHT / (meters * BOLTZMANN * temperature);
+    numerator / exponent.exp_m1() * 1.0e-9
+}
+
+/// CIE 1931 2 degree observer at `wavelength` nanometers, using the multi-lobe
+/// fit from Wyman, Sloan and Shirley's "Simple Analytic Approximations to the
+/// CIE XYZ Color Matching Functions".
+pub(crate) fn color_matching(wavelength: f64) -> (f64, f64, f64) {
+    let lobe = |mean: f64, below: f64, above: f64| {
+        let t = (wavelength - mean) / if wavelength < mean { below } else { above };
+        (-0.5 * t * t).exp()
+    };
+
+    (
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chromaticity(color: Color) -> (f64, f64) {
+        let (x, y, z) = color.to_xyz();
+        (x / (x + y + z), y / (x + y + z))
+    }
+
+    #[test]
+    fn blackbody_chromaticity_follows_the_planckian_locus() {
+        // CIE 1931 chromaticities of the Planckian locus
+        for (temperature, expected) in
+            [(2700.0, (0.4599, 0.4106)), (6500.0, (0.3135, 0.3237))].iter()
+        {
+            let (x, y) = chromaticity(Color::from_kelvin(*temperature));
+            assert!((x - expected.0).abs() < 2.0e-3, "{} {}", temperature, x);
+            assert!((y - expected.1).abs() < 2.0e-3, "{} {}", temperature, y);
+        }
+    }
+
+    #[test]
+    fn normalizing_blackbody_colors() {
+        let warm = Color::from_kelvin(2700.0);
+        assert!((warm.to_xyz().1 - 1.0).abs() < 1.0e-9);
+        assert!(warm.red > warm.green && warm.green > warm.blue);
+
+        let cool = Color::from_kelvin_normalized(12000.0, BlackbodyNormalization::MaxComponent);
+        assert!((cool.blue - 1.0).abs() < 1.0e-9);
+        assert!(cool.red < 1.0);
+    }
+
+    #[test]
+    fn white_balancing_neutralizes_a_warm_light() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::from_kelvin(2700.0) * 0.5);
+        c.write_pixel(1, 0, Color::new(0.2, 0.4, 0.6));
+
+        let balanced = c.white_balance(2700.0, 2700.0);
+        assert_eq!(balanced.pixel_at(1, 0), Color::new(0.2, 0.4, 0.6));
+
+        let balanced = c.white_balance(2700.0, 6504.0);
+        assert_eq!(balanced.pixel_at(0, 0), Color::from_kelvin(6504.0) * 0.5);
+        assert!(balanced.pixel_at(1, 0).blue > 0.6);
+    }
+
+    #[test]
+    fn candle_light_has_no_blue() {
+        let candle = Color::from_kelvin_normalized(1000.0, BlackbodyNormalization::MaxComponent);
+
+        assert_eq!(candle.blue, 0.0);
+        assert!((candle.red - 1.0).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn planck_peak_follows_wiens_law() {
+        // the peak of a 5000 K radiator lies near 580 nm
+        assert!(planck(580.0, 5000.0) > planck(500.0, 5000.0));
+        assert!(planck(580.0, 5000.0) > planck(660.0, 5000.0));
+        assert!(planck(550.0, 6000.0) > planck(550.0, 3000.0));
+    }
+}
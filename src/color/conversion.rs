@@ -0,0 +1,419 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::color::{Color, TransferFunction};
+
+// linear sRGB to CIE XYZ, both relative to D65
+const RGB_TO_XYZ: [[f64; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.0721750],
+    [0.0193339, 0.1191920, 0.9503041],
+];
+
+const XYZ_TO_RGB: [[f64; 3]; 3] = [
+    [3.2404542, -1.5371385, -0.4985314],
+    [-0.9692660, 1.8760108, 0.0415560],
+    [0.0556434, -0.2040259, 1.0572252],
+];
+
+/// D65 reference white in CIE XYZ.
+pub(crate) const D65: (f64, f64, f64) = (0.95047, 1.0, 1.08883);
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseColorError {
+    MissingDigits,
+    InvalidDigit,
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseColorError::MissingDigits => write!(f, "expected 3 or 6 hex digits"),
+            ParseColorError::InvalidDigit => write!(f, "invalid hex digit"),
+        }
+    }
+}
+
+impl Error for ParseColorError {}
+
+// HSV, HSL and hex describe display values, so they are sRGB encoded and the
+// color is linear. Hues are in degrees.
+impl Color {
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Color {
+        let chroma = value * saturation;
+        Color::from_hue(hue, chroma, value - chroma)
+    }
+
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (hue, min, max) = self.hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (hue, min, max) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Parses `#rrggbb` or `#rgb`, the leading `#` is optional.
+    pub fn from_hex(hex: &str) -> Result<Color, ParseColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let values = digits
+            .chars()
+            .map(|c| c.to_digit(16).ok_or(ParseColorError::InvalidDigit))
+            .collect::<Result<Vec<u32>, ParseColorError>>()?;
+
+        let channels = match values.len() {
+            3 => [values[0] * 17, values[1] * 17, values[2] * 17],
+            6 => [
+                values[0] * 16 + values[1],
+                values[2] * 16 + values[3],
+                values[4] * 16 + values[5],
+            ],
+            _ => return Err(ParseColorError::MissingDigits),
+        };
+
+        Ok(TransferFunction::Srgb.decode_color(Color::new(
+            channels[0] as f64 / 255.0,
+            channels[1] as f64 / 255.0,
+            channels[2] as f64 / 255.0,
+        )))
+    }
+
+    pub fn to_hex(&self) -> String {
+        let encoded = TransferFunction::Srgb.encode_color(*self);
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            Color::convert_component(encoded.red),
+            Color::convert_component(encoded.green),
+            Color::convert_component(encoded.blue)
+        )
+    }
+
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Color {
+        let [red, green, blue] = multiply(XYZ_TO_RGB, [x, y, z]);
+        Color::new(red, green, blue)
+    }
+
+    pub fn to_xyz(&self) -> (f64, f64, f64) {
+        let [x, y, z] = multiply(RGB_TO_XYZ, [self.red, self.green, self.blue]);
+        (x, y, z)
+    }
+
+    /// CIELAB relative to D65, with L* from 0 to 100.
+    pub fn from_lab(l: f64, a: f64, b: f64) -> Color {
+        let inverse = |t: f64| {
+            if t > DELTA {
+                t * t * t
+            } else {
+                3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+            }
+        };
+
+        let y = (l + 16.0) / 116.0;
+        Color::from_xyz(
+            D65.0 * inverse(y + a / 500.0),
+            D65.1 * inverse(y),
+            D65.2 * inverse(y - b / 200.0),
+        )
+    }
+
+    pub fn to_lab(&self) -> (f64, f64, f64) {
+        let f = |t: f64| {
+            if t > DELTA * DELTA * DELTA {
+                t.cbrt()
+            } else {
+                t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+            }
+        };
+
+        let (x, y, z) = self.to_xyz();
+        let (fx, fy, fz) = (f(x / D65.0), f(y / D65.1), f(z / D65.2));
+        (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
+    /// Cylindrical CIELAB as lightness, chroma and hue.
+    pub fn from_lch(l: f64, chroma: f64, hue: f64) -> Color {
+        let (sin, cos) = hue.to_radians().sin_cos();
+        Color::from_lab(l, chroma * cos, chroma * sin)
+    }
+
+    pub fn to_lch(&self) -> (f64, f64, f64) {
+        let (l, a, b) = self.to_lab();
+        (l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0))
+    }
+
+    /// Björn Ottosson's OKLab, a perceptual space that keeps hues straight
+    /// when blending.
+    pub fn from_oklab(l: f64, a: f64, b: f64) -> Color {
+        let cube = |v: f64| v * v * v;
+        let long = cube(l + 0.3963377774 * a + 0.2158037573 * b);
+        let medium = cube(l - 0.1055613458 * a - 0.0638541728 * b);
+        let short = cube(l - 0.0894841775 * a - 1.2914855480 * b);
+
+        Color::new(
+            4.0767416621 * long - 3.3077115913 * medium + 0.2309699292 * short,
+            -1.2684380046 * long + 2.6097574011 * medium - 0.3413193965 * short,
+            -0.0041960863 * long - 0.7034186147 * medium + 1.7076147010 * short,
+        )
+    }
+
+    pub fn to_oklab(&self) -> (f64, f64, f64) {
+        let Color { red, green, blue } = *self;
+        let long = (0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue).cbrt();
+        let medium = (0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue).cbrt();
+        let short = (0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue).cbrt();
+
+        (
+            0.2104542553 * long + 0.7936177850 * medium - 0.0040720468 * short,
+            1.9779984951 * long - 2.4285922050 * medium + 0.4505937099 * short,
+            0.0259040371 * long + 0.7827717662 * medium - 0.8086757660 * short,
+        )
+    }
+
+    /// CIEDE2000 color difference, where about 1.0 is just noticeable.
+    pub fn delta_e(&self, other: &Color) -> f64 {
+        let (l1, a1, b1) = self.to_lab();
+        let (l2, a2, b2) = other.to_lab();
+
+        let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+        let g = 0.5 * (1.0 - (c_mean.powi(7) / (c_mean.powi(7) + 25f64.powi(7))).sqrt());
+        let (a1, a2) = (a1 * (1.0 + g), a2 * (1.0 + g));
+        let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+        let hue = |a: f64, b: f64| {
+            if a == 0.0 && b == 0.0 {
+                0.0
+            } else {
+                b.atan2(a).to_degrees().rem_euclid(360.0)
+            }
+        };
+        let (h1, h2) = (hue(a1, b1), hue(a2, b2));
+
+        let delta_l = l2 - l1;
+        let delta_c = c2 - c1;
+        let delta_h = if c1 * c2 == 0.0 {
+            0.0
+        } else if (h2 - h1).abs() <= 180.0 {
+            h2 - h1
+        } else if h2 <= h1 {
+            h2 - h1 + 360.0
+        } else {
+            h2 - h1 - 360.0
+        };
+        let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+        let l_mean = (l1 + l2) / 2.0;
+        let c_mean = (c1 + c2) / 2.0;
+        let h_mean = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+            + 0.24 * (2.0 * h_mean).to_radians().cos()
+            + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+            - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+        let delta_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+        let r_c = 2.0 * (c_mean.powi(7) / (c_mean.powi(7) + 25f64.powi(7))).sqrt();
+        let s_l = 1.0 + 0.015 * (l_mean - 50.0).powi(2) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+        let s_c = 1.0 + 0.045 * c_mean;
+        let s_h = 1.0 + 0.015 * c_mean * t;
+        let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+        let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+        (l * l + c * c + h * h + r_t * c * h).sqrt()
+    }
+
+    fn from_hue(hue: f64, chroma: f64, offset: f64) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as usize {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        TransferFunction::Srgb.decode_color(Color::new(r + offset, g + offset, b + offset))
+    }
+
+    // hue in degrees with the smallest and largest encoded component
+    fn hue(&self) -> (f64, f64, f64) {
+        let Color { red, green, blue } = TransferFunction::Srgb.encode_color(*self);
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let chroma = max - min;
+
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / chroma).rem_euclid(6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / chroma + 2.0)
+        } else {
+            60.0 * ((red - green) / chroma + 4.0)
+        };
+        (hue, min, max)
+    }
+}
+
+const DELTA: f64 = 6.0 / 29.0;
+
+fn multiply(m: [[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(actual: (f64, f64, f64), expected: (f64, f64, f64), tolerance: f64) {
+        assert!(
+            (actual.0 - expected.0).abs() < tolerance
+                && (actual.1 - expected.1).abs() < tolerance
+                && (actual.2 - expected.2).abs() < tolerance,
+            "{:?} is not close to {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn converting_hsv() {
+        let orange = Color::from_hsv(30.0, 1.0, 1.0);
+
+        assert_eq!(orange.to_hex(), "#ff8000");
+        assert_close(orange.to_hsv(), (30.0, 1.0, 1.0), 1.0e-9);
+        assert_close(Color::black().to_hsv(), (0.0, 0.0, 0.0), 1.0e-12);
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn converting_hsl() {
+        let pink = Color::from_hsl(330.0, 1.0, 0.75);
+
+        assert_eq!(pink.to_hex(), "#ff80bf");
+        assert_close(pink.to_hsl(), (330.0, 1.0, 0.75), 1.0e-9);
+        assert_close(Color::new(1.0, 1.0, 1.0).to_hsl(), (0.0, 0.0, 1.0), 1.0e-12);
+    }
+
+    #[test]
+    fn parsing_hex_colors() {
+        assert_eq!(Color::from_hex("#ffffff"), Ok(Color::new(1.0, 1.0, 1.0)));
+        assert_eq!(Color::from_hex("f00"), Ok(Color::new(1.0, 0.0, 0.0)));
+        assert_eq!(Color::from_hex("#bada55").unwrap().to_hex(), "#bada55");
+        assert_eq!(
+            Color::from_hex("#12345"),
+            Err(ParseColorError::MissingDigits)
+        );
+        assert_eq!(
+            Color::from_hex("#12345g"),
+            Err(ParseColorError::InvalidDigit)
+        );
+    }
+
+    #[test]
+    fn converting_xyz() {
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        assert_close(white.to_xyz(), D65, 1.0e-4);
+        let (x, y, z) = Color::new(0.2, 0.5, 0.7).to_xyz();
+        assert_eq!(Color::from_xyz(x, y, z), Color::new(0.2, 0.5, 0.7));
+    }
+
+    #[test]
+    fn converting_lab_and_lch() {
+        assert_close(
+            Color::new(1.0, 1.0, 1.0).to_lab(),
+            (100.0, 0.0, 0.0),
+            1.0e-3,
+        );
+        // sRGB red, as listed by the usual online converters
+        assert_close(
+            Color::new(1.0, 0.0, 0.0).to_lab(),
+            (53.24, 80.09, 67.20),
+            0.01,
+        );
+
+        let color = Color::new(0.1, 0.6, 0.3);
+        let (l, a, b) = color.to_lab();
+        assert_eq!(Color::from_lab(l, a, b), color);
+        let (l, c, h) = color.to_lch();
+        assert_eq!(Color::from_lch(l, c, h), color);
+    }
+
+    #[test]
+    fn converting_oklab() {
+        assert_close(
+            Color::new(1.0, 1.0, 1.0).to_oklab(),
+            (1.0, 0.0, 0.0),
+            1.0e-4,
+        );
+        // sRGB red from Ottosson's reference values
+        assert_close(
+            Color::new(1.0, 0.0, 0.0).to_oklab(),
+            (0.628, 0.2249, 0.1258),
+            1.0e-3,
+        );
+
+        let (l, a, b) = Color::new(0.3, 0.6, 0.1).to_oklab();
+        assert_eq!(Color::from_oklab(l, a, b), Color::new(0.3, 0.6, 0.1));
+    }
+
+    #[test]
+    fn ciede2000_reference_pairs() {
+        // pairs from Sharma, Wu and Dalal's CIEDE2000 test data
+        let pairs = [
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, -1.0, 2.0), (50.0, 0.0, 0.0), 2.3669),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            (
+                (60.2574, -34.0099, 36.2677),
+                (60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+            (
+                (22.7233, 20.0904, -46.6940),
+                (23.0331, 14.9730, -42.5619),
+                2.0373,
+            ),
+        ];
+
+        for ((l1, a1, b1), (l2, a2, b2), expected) in pairs.iter() {
+            let first = Color::from_lab(*l1, *a1, *b1);
+            let second = Color::from_lab(*l2, *a2, *b2);
+            let delta = first.delta_e(&second);
+            assert!(
+                (delta - expected).abs() < 1.0e-3,
+                "{} != {}",
+                delta,
+                expected
+            );
+        }
+        assert_eq!(
+            Color::new(0.3, 0.2, 0.1).delta_e(&Color::new(0.3, 0.2, 0.1)),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use crate::color::{Color, TransferFunction};
+
+// matplotlib's viridis at eleven evenly spaced positions
+const VIRIDIS: [&str; 11] = [
+    "#440154", "#482475", "#414487", "#355f8d", "#2a788e", "#21918c", "#22a884", "#44bf70",
+    "#7ad151", "#bddf26", "#fde725",
+];
+
+const TURBO_STOPS: usize = 33;
+
+/// Space in which neighbouring stops are blended.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RampSpace {
+    LinearRgb,
+    Srgb,
+    Oklab,
+    /// Blends hues along the shorter way around the color wheel.
+    Hsv,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RampMode {
+    /// Holds the color of the stop below until the next stop.
+    Constant,
+    Linear,
+    /// Eases in and out of every stop.
+    Smoothstep,
+}
+
+/// Maps scalars to colors through positioned stops. Positions outside the
+/// first and last stop take the color of the nearest stop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, Color)>,
+    space: RampSpace,
+    mode: RampMode,
+}
+
+impl ColorRamp {
+    pub fn new(mut stops: Vec<(f64, Color)>) -> ColorRamp {
+        assert!(!stops.is_empty()); // TODO: real error handling
+        assert!(stops.iter().all(|(position, _)| position.is_finite()));
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        ColorRamp {
+            stops,
+            space: RampSpace::LinearRgb,
+            mode: RampMode::Linear,
+        }
+    }
+
+    pub fn space(mut self, space: RampSpace) -> ColorRamp {
+        self.space = space;
+        self
+    }
+
+    pub fn mode(mut self, mode: RampMode) -> ColorRamp {
+        self.mode = mode;
+        self
+    }
+
+    /// Perceptually uniform dark blue to yellow map from matplotlib.
+    pub fn viridis() -> ColorRamp {
+        let last = (VIRIDIS.len() - 1) as f64;
+        let stops = VIRIDIS
+            .iter()
+            .enumerate()
+            .map(|(index, hex)| (index as f64 / last, Color::from_hex(hex).unwrap()))
+            .collect();
+        ColorRamp::new(stops).space(RampSpace::Srgb)
+    }
+
+    /// Google's rainbow map, from Anton Mikhailov's polynomial fit.
+    pub fn turbo() -> ColorRamp {
+        let last = (TURBO_STOPS - 1) as f64;
+        let stops = (0..TURBO_STOPS)
+            .map(|index| {
+                let t = index as f64 / last;
+                (t, TransferFunction::Srgb.decode_color(turbo(t)))
+            })
+            .collect();
+        ColorRamp::new(stops).space(RampSpace::Srgb)
+    }
+
+    pub fn sample(&self, position: f64) -> Color {
+        let upper = self.stops.iter().position(|(p, _)| *p > position);
+        let (start, end) = match upper {
+            Some(0) => return self.stops[0].1,
+            None => return self.stops[self.stops.len() - 1].1,
+            Some(index) => (self.stops[index - 1], self.stops[index]),
+        };
+
+        let t = (position - start.0) / (end.0 - start.0);
+        let t = match self.mode {
+            RampMode::Constant => return start.1,
+            RampMode::Linear => t,
+            RampMode::Smoothstep => t * t * (3.0 - 2.0 * t),
+        };
+        self.blend(start.1, end.1, t)
+    }
+
+    fn blend(&self, from: Color, to: Color, t: f64) -> Color {
+        match self.space {
+            RampSpace::LinearRgb => from.lerp(to, t),
+            RampSpace::Srgb => {
+                let srgb = TransferFunction::Srgb;
+                srgb.decode_color(srgb.encode_color(from).lerp(srgb.encode_color(to), t))
+            }
+            RampSpace::Oklab => {
+                let (l1, a1, b1) = from.to_oklab();
+                let (l2, a2, b2) = to.to_oklab();
+                let mix = |a: f64, b: f64| a + (b - a) * t;
+                Color::from_oklab(mix(l1, l2), mix(a1, a2), mix(b1, b2))
+            }
+            RampSpace::Hsv => {
+                let (h1, s1, v1) = from.to_hsv();
+                let (h2, s2, v2) = to.to_hsv();
+                // greys have no hue of their own, take the other stop's
+                let h1 = if s1 == 0.0 { h2 } else { h1 };
+                let h2 = if s2 == 0.0 { h1 } else { h2 };
+                let delta = (h2 - h1 + 180.0).rem_euclid(360.0) - 180.0;
+                let mix = |a: f64, b: f64| a + (b - a) * t;
+                Color::from_hsv(h1 + delta * t, mix(s1, s2), mix(v1, v2))
+            }
+        }
+    }
+}
+
+// display encoded turbo color at t in [0, 1]
+fn turbo(t: f64) -> Color {
+    let polynomial = |c: [f64; 6]| {
+        let value = c[0] + t * (c[1] + t * (c[2] + t * (c[3] + t * (c[4] + t * c[5]))));
+        value.clamp(0.0, 1.0)
+    };
+
+    Color::new(
+        polynomial([
+            0.13572138,
+            4.61539260,
+            -42.66032258,
+            132.13108234,
+            -152.94239396,
+            59.28637943,
+        ]),
+        polynomial([
+            0.09140261,
+            2.19418839,
+            4.84296658,
+            -14.18503333,
+            4.27729857,
+            2.82956604,
+        ]),
+        polynomial([
+            0.10667330,
+            12.64194608,
+            -60.58204836,
+            110.36276771,
+            -89.90310912,
+            27.34824973,
+        ]),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn black_to_white() -> ColorRamp {
+        ColorRamp::new(vec![
+            (1.0, Color::new(1.0, 1.0, 1.0)),
+            (0.0, Color::black()),
+        ])
+    }
+
+    #[test]
+    fn sampling_between_stops() {
+        let ramp = black_to_white();
+
+        assert_eq!(ramp.sample(0.25), Color::new(0.25, 0.25, 0.25));
+        assert_eq!(ramp.sample(-1.0), Color::black());
+        assert_eq!(ramp.sample(2.0), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn interpolation_modes() {
+        let constant = black_to_white().mode(RampMode::Constant);
+        assert_eq!(constant.sample(0.9), Color::black());
+        assert_eq!(constant.sample(1.0), Color::new(1.0, 1.0, 1.0));
+
+        let smooth = black_to_white().mode(RampMode::Smoothstep);
+        assert_eq!(smooth.sample(0.25), Color::new(0.15625, 0.15625, 0.15625));
+        assert_eq!(smooth.sample(0.5), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn interpolation_spaces() {
+        let srgb = black_to_white().space(RampSpace::Srgb);
+        assert_eq!(srgb.sample(0.25).to_hex(), "#404040");
+
+        let oklab = black_to_white().space(RampSpace::Oklab);
+        assert!((oklab.sample(0.5).to_oklab().0 - 0.5).abs() < 1.0e-6);
+
+        // red to blue through magenta rather than green
+        let hsv = ColorRamp::new(vec![
+            (0.0, Color::new(1.0, 0.0, 0.0)),
+            (1.0, Color::new(0.0, 0.0, 1.0)),
+        ])
+        .space(RampSpace::Hsv);
+        assert_eq!(hsv.sample(0.5), Color::new(1.0, 0.0, 1.0));
+
+        // black has no hue, so the blend stays blue instead of passing red
+        let hsv = ColorRamp::new(vec![
+            (0.0, Color::black()),
+            (1.0, Color::new(0.0, 0.0, 1.0)),
+        ])
+        .space(RampSpace::Hsv);
+        let dark_blue = hsv.sample(0.25);
+        assert_eq!(dark_blue.red, dark_blue.green);
+        assert!(dark_blue.blue > dark_blue.red);
+    }
+
+    #[test]
+    #[should_panic]
+    fn stops_must_have_finite_positions() {
+        ColorRamp::new(vec![(0.0, Color::black()), (f64::NAN, Color::black())]);
+    }
+
+    #[test]
+    fn built_in_palettes() {
+        let viridis = ColorRamp::viridis();
+        assert_eq!(viridis.sample(0.0).to_hex(), "#440154");
+        assert_eq!(viridis.sample(0.5).to_hex(), "#21918c");
+        assert_eq!(viridis.sample(1.0).to_hex(), "#fde725");
+
+        let turbo = ColorRamp::turbo();
+        assert_eq!(turbo.sample(0.0).to_hex(), "#23171b");
+        let middle = turbo.sample(0.5);
+        assert!(middle.green > middle.red && middle.green > middle.blue);
+    }
+}
@@ -0,0 +1,385 @@
+use std::ops;
+
+use crate::color::blackbody::{color_matching, planck};
+use crate::color::{Color, D65};
+
+pub const MIN_WAVELENGTH: f64 = 380.0;
+pub const MAX_WAVELENGTH: f64 = 780.0;
+
+const STEP: f64 = 5.0;
+const SAMPLES: usize = 81;
+
+// bins of 34 nm starting at 380 nm, from Smits' "An RGB to Spectrum Conversion
+// for Reflectances"
+const SMITS_BINS: usize = 10;
+const SMITS_START: f64 = 380.0;
+const SMITS_END: f64 = 720.0;
+const SMITS_WHITE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; SMITS_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; SMITS_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; SMITS_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; SMITS_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// Values sampled every 5 nm from 380 nm to 780 nm, linearly interpolated in
+/// between.
+///
+/// This is the color side of spectral rendering only. The crate has no
+/// renderer yet, so nothing traces rays with spectra or hero wavelengths.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spectrum {
+    samples: [f64; SAMPLES],
+}
+
+impl Spectrum {
+    pub fn constant(value: f64) -> Spectrum {
+        Spectrum {
+            samples: [value; SAMPLES],
+        }
+    }
+
+    /// Samples `f` at every wavelength in nanometers.
+    pub fn from_fn<F: Fn(f64) -> f64>(f: F) -> Spectrum {
+        let mut samples = [0.0; SAMPLES];
+        for (index, sample) in samples.iter_mut().enumerate() {
+            *sample = f(wavelength(index));
+        }
+        Spectrum { samples }
+    }
+
+    /// Emission of a blackbody with its peak normalized to one.
+    pub fn blackbody(temperature: f64) -> Spectrum {
+        let spectrum = Spectrum::from_fn(|lambda| planck(lambda, temperature));
+        let peak = spectrum.samples.iter().cloned().fold(0.0, f64::max);
+        spectrum * (1.0 / peak)
+    }
+
+    /// A smooth reflectance spectrum reproducing `color`, using Smits' method.
+    /// Colors within `[0, 1]` give values within `[0, 1]`, roughly.
+    pub fn from_color(color: Color) -> Spectrum {
+        let Color { red, green, blue } = color;
+        let bins = |weights: &[(f64, &[f64; SMITS_BINS])]| {
+            Spectrum::from_fn(|lambda| {
+                let position = (lambda - SMITS_START) / (SMITS_END - SMITS_START);
+                let bin = ((position * SMITS_BINS as f64).max(0.0) as usize).min(SMITS_BINS - 1);
+                weights
+                    .iter()
+                    .map(|(weight, curve)| weight * curve[bin])
+                    .sum()
+            })
+        };
+
+        if red <= green && red <= blue {
+            if green <= blue {
+                bins(&[
+                    (red, &SMITS_WHITE),
+                    (green - red, &SMITS_CYAN),
+                    (blue - green, &SMITS_BLUE),
+                ])
+            } else {
+                bins(&[
+                    (red, &SMITS_WHITE),
+                    (blue - red, &SMITS_CYAN),
+                    (green - blue, &SMITS_GREEN),
+                ])
+            }
+        } else if green <= red && green <= blue {
+            if red <= blue {
+                bins(&[
+                    (green, &SMITS_WHITE),
+                    (red - green, &SMITS_MAGENTA),
+                    (blue - red, &SMITS_BLUE),
+                ])
+            } else {
+                bins(&[
+                    (green, &SMITS_WHITE),
+                    (blue - green, &SMITS_MAGENTA),
+                    (red - blue, &SMITS_RED),
+                ])
+            }
+        } else if red <= green {
+            bins(&[
+                (blue, &SMITS_WHITE),
+                (red - blue, &SMITS_YELLOW),
+                (green - red, &SMITS_GREEN),
+            ])
+        } else {
+            bins(&[
+                (blue, &SMITS_WHITE),
+                (green - blue, &SMITS_YELLOW),
+                (red - green, &SMITS_RED),
+            ])
+        }
+    }
+
+    /// Value at `wavelength` nanometers, zero outside the sampled range.
+    pub fn sample(&self, wavelength: f64) -> f64 {
+        if !(MIN_WAVELENGTH..=MAX_WAVELENGTH).contains(&wavelength) {
+            return 0.0;
+        }
+
+        let position = (wavelength - MIN_WAVELENGTH) / STEP;
+        let index = (position as usize).min(SAMPLES - 2);
+        let t = position - index as f64;
+        self.samples[index] * (1.0 - t) + self.samples[index + 1] * t
+    }
+
+    /// CIE XYZ with a constant spectrum of one having a luminance of one.
+    pub fn to_xyz(&self) -> (f64, f64, f64) {
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        let mut normalization = 0.0;
+        for (index, sample) in self.samples.iter().enumerate() {
+            let (cx, cy, cz) = color_matching(wavelength(index));
+            x += sample * cx;
+            y += sample * cy;
+            z += sample * cz;
+            normalization += cy;
+        }
+        (x / normalization, y / normalization, z / normalization)
+    }
+
+    /// Converts through CIE XYZ, adapting the equal energy white of a constant
+    /// spectrum to the white of `Color` by scaling X and Z. This is a plain
+    /// per-channel scale in XYZ, not a Bradford adaptation like
+    /// [`WhitePoint::adaptation_to`](crate::WhitePoint::adaptation_to).
+    pub fn to_color(&self) -> Color {
+        let (x, y, z) = self.to_xyz();
+        let (white_x, _, white_z) = equal_energy_white();
+        Color::from_xyz(x * D65.0 / white_x, y, z * D65.2 / white_z)
+    }
+}
+
+impl ops::Add<Spectrum> for Spectrum {
+    type Output = Self;
+
+    fn add(mut self, other: Spectrum) -> Self::Output {
+        for (sample, other) in self.samples.iter_mut().zip(other.samples.iter()) {
+            *sample += other;
+        }
+        self
+    }
+}
+
+impl ops::Mul<Spectrum> for Spectrum {
+    type Output = Self;
+
+    fn mul(mut self, other: Spectrum) -> Self::Output {
+        for (sample, other) in self.samples.iter_mut().zip(other.samples.iter()) {
+            *sample *= other;
+        }
+        self
+    }
+}
+
+impl ops::Mul<f64> for Spectrum {
+    type Output = Self;
+
+    fn mul(mut self, other: f64) -> Self::Output {
+        for sample in self.samples.iter_mut() {
+            *sample *= other;
+        }
+        self
+    }
+}
+
+/// Number of wavelengths carried by a path in hero wavelength sampling.
+pub const HERO_WAVELENGTHS: usize = 4;
+
+/// Wavelengths traced together along one path, after Wilkie et al.'s "Hero
+/// Wavelength Spectral Sampling". The hero is chosen uniformly and the others
+/// are spread evenly across the visible range. Dispersive events, where the
+/// path depends on the wavelength, keep only the hero.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HeroWavelengths {
+    wavelengths: [f64; HERO_WAVELENGTHS],
+    secondary_terminated: bool,
+}
+
+impl HeroWavelengths {
+    /// Picks wavelengths from a uniform random number `u` in `[0, 1)`.
+    pub fn sample(u: f64) -> HeroWavelengths {
+        let range = MAX_WAVELENGTH - MIN_WAVELENGTH;
+        let mut wavelengths = [0.0; HERO_WAVELENGTHS];
+        for (j, lambda) in wavelengths.iter_mut().enumerate() {
+            let offset = u * range + j as f64 * range / HERO_WAVELENGTHS as f64;
+            *lambda = MIN_WAVELENGTH + offset % range;
+        }
+
+        HeroWavelengths {
+            wavelengths,
+            secondary_terminated: false,
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.wavelengths[0]
+    }
+
+    pub fn wavelengths(&self) -> &[f64; HERO_WAVELENGTHS] {
+        &self.wavelengths
+    }
+
+    pub fn terminate_secondary(&mut self) {
+        self.secondary_terminated = true;
+    }
+
+    pub fn is_secondary_terminated(&self) -> bool {
+        self.secondary_terminated
+    }
+
+    /// Values of `spectrum` at each wavelength.
+    pub fn sample_spectrum(&self, spectrum: &Spectrum) -> [f64; HERO_WAVELENGTHS] {
+        let mut values = [0.0; HERO_WAVELENGTHS];
+        for (value, lambda) in values.iter_mut().zip(self.wavelengths.iter()) {
+            *value = spectrum.sample(*lambda);
+        }
+        values
+    }
+
+    /// Monte Carlo estimate of the color of a path carrying `values`, with
+    /// the same normalization as [`Spectrum::to_color`]. Averaging the
+    /// estimates of many paths converges to the color of the spectrum.
+    pub fn to_color(&self, values: &[f64; HERO_WAVELENGTHS]) -> Color {
+        let count = if self.secondary_terminated {
+            1
+        } else {
+            HERO_WAVELENGTHS
+        };
+
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        for (value, lambda) in values.iter().zip(self.wavelengths.iter()).take(count) {
+            let (cx, cy, cz) = color_matching(*lambda);
+            x += value * cx;
+            y += value * cy;
+            z += value * cz;
+        }
+
+        // uniform density over the range, relative to the integral of y
+        let scale = (MAX_WAVELENGTH - MIN_WAVELENGTH) / (count as f64 * luminance_integral());
+        let (white_x, _, white_z) = equal_energy_white();
+        Color::from_xyz(
+            x * scale * D65.0 / white_x,
+            y * scale,
+            z * scale * D65.2 / white_z,
+        )
+    }
+}
+
+fn wavelength(index: usize) -> f64 {
+    MIN_WAVELENGTH + index as f64 * STEP
+}
+
+// integral of the luminous efficiency over the sampled range, in nm
+fn luminance_integral() -> f64 {
+    (0..SAMPLES)
+        .map(|index| color_matching(wavelength(index)).1 * STEP)
+        .sum()
+}
+
+fn equal_energy_white() -> (f64, f64, f64) {
+    Spectrum::constant(1.0).to_xyz()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sampling_a_spectrum() {
+        let ramp = Spectrum::from_fn(|lambda| lambda / 1000.0);
+
+        assert!((ramp.sample(380.0) - 0.38).abs() < 1.0e-12);
+        assert!((ramp.sample(512.5) - 0.5125).abs() < 1.0e-12);
+        assert!((ramp.sample(780.0) - 0.78).abs() < 1.0e-12);
+        assert_eq!(ramp.sample(800.0), 0.0);
+    }
+
+    #[test]
+    fn constant_spectrum_is_white() {
+        assert_eq!(
+            Spectrum::constant(1.0).to_color(),
+            Color::new(1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            Spectrum::constant(0.5).to_color(),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert!((Spectrum::constant(1.0).to_xyz().1 - 1.0).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn converting_colors_to_spectra_and_back() {
+        for color in [
+            Color::new(0.8, 0.2, 0.1),
+            Color::new(0.1, 0.7, 0.3),
+            Color::new(0.2, 0.3, 0.9),
+            Color::new(0.5, 0.5, 0.5),
+        ]
+        .iter()
+        {
+            let spectrum = Spectrum::from_color(*color);
+            let converted = spectrum.to_color();
+            assert!(
+                color.delta_e(&converted) < 5.0,
+                "{:?} became {:?}",
+                color,
+                converted
+            );
+        }
+    }
+
+    #[test]
+    fn spectral_arithmetic() {
+        let a = Spectrum::constant(0.5);
+        let b = Spectrum::from_fn(|lambda| if lambda < 500.0 { 1.0 } else { 0.0 });
+
+        assert_eq!((a * b).sample(400.0), 0.5);
+        assert_eq!((a * b).sample(600.0), 0.0);
+        assert_eq!((a + b * 2.0).sample(400.0), 2.5);
+    }
+
+    #[test]
+    fn hero_wavelengths_cover_the_range() {
+        let hero = HeroWavelengths::sample(0.9);
+
+        assert_eq!(hero.hero(), 740.0);
+        assert_eq!(hero.wavelengths(), &[740.0, 440.0, 540.0, 640.0]);
+    }
+
+    #[test]
+    fn hero_wavelength_estimates_converge() {
+        let spectrum = Spectrum::from_color(Color::new(0.7, 0.4, 0.2));
+        let paths = 1000;
+
+        let estimate = (0..paths)
+            .map(|i| {
+                let hero = HeroWavelengths::sample((i as f64 + 0.5) / paths as f64);
+                hero.to_color(&hero.sample_spectrum(&spectrum))
+            })
+            .sum::<Color>()
+            / paths as f64;
+        assert!(estimate.delta_e(&spectrum.to_color()) < 0.5);
+
+        let mut dispersed = HeroWavelengths::sample(0.25);
+        dispersed.terminate_secondary();
+        let values = dispersed.sample_spectrum(&Spectrum::constant(1.0));
+        let (_, y, _) = dispersed.to_color(&values).to_xyz();
+        let expected = color_matching(dispersed.hero()).1 * 400.0 / luminance_integral();
+        assert!((y - expected).abs() < 1.0e-5);
+    }
+}
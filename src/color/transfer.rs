@@ -0,0 +1,111 @@
+use crate::color::Color;
+
+/// Maps between linear light and the non-linear values stored in 8-bit images.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TransferFunction {
+    Linear,
+    Srgb,
+    Rec709,
+    Gamma(f64),
+}
+
+impl TransferFunction {
+    pub fn encode(&self, value: f64) -> f64 {
+        match *self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb => {
+                if value <= 0.003_130_8 {
+                    value * 12.92
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                }
+            }
+            TransferFunction::Rec709 => {
+                if value < 0.018 {
+                    value * 4.5
+                } else {
+                    1.099 * value.powf(0.45) - 0.099
+                }
+            }
+            TransferFunction::Gamma(gamma) => value.signum() * value.abs().powf(1.0 / gamma),
+        }
+    }
+
+    pub fn decode(&self, value: f64) -> f64 {
+        match *self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb => {
+                if value <= 0.040_45 {
+                    value / 12.92
+                } else {
+                    ((value + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            TransferFunction::Rec709 => {
+                if value < 0.081 {
+                    value / 4.5
+                } else {
+                    ((value + 0.099) / 1.099).powf(1.0 / 0.45)
+                }
+            }
+            TransferFunction::Gamma(gamma) => value.signum() * value.abs().powf(gamma),
+        }
+    }
+
+    pub fn encode_color(&self, color: Color) -> Color {
+        Color::new(
+            self.encode(color.red),
+            self.encode(color.green),
+            self.encode(color.blue),
+        )
+    }
+
+    pub fn decode_color(&self, color: Color) -> Color {
+        Color::new(
+            self.decode(color.red),
+            self.decode(color.green),
+            self.decode(color.blue),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn srgb_encoding() {
+        assert_eq!(TransferFunction::Srgb.encode(0.0), 0.0);
+        assert!((TransferFunction::Srgb.encode(1.0) - 1.0).abs() < 1.0e-9);
+        assert!((TransferFunction::Srgb.encode(0.214_041) - 0.5).abs() < 1.0e-5);
+        assert!((TransferFunction::Srgb.encode(0.001) - 0.012_92).abs() < 1.0e-9);
+    }
+
+    #[test]
+    fn decoding_inverts_encoding() {
+        let functions = [
+            TransferFunction::Linear,
+            TransferFunction::Srgb,
+            TransferFunction::Rec709,
+            TransferFunction::Gamma(2.2),
+        ];
+
+        for function in functions.iter() {
+            for i in 0..=20 {
+                let value = i as f64 / 20.0;
+                assert!((function.decode(function.encode(value)) - value).abs() < 1.0e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn encoding_colors() {
+        let encoded = TransferFunction::Gamma(2.0).encode_color(Color::new(0.25, 1.0, 0.0));
+
+        assert_eq!(encoded, Color::new(0.5, 1.0, 0.0));
+        assert_eq!(
+            TransferFunction::Gamma(2.0).decode_color(encoded),
+            Color::new(0.25, 1.0, 0.0)
+        );
+    }
+}
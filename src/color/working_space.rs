@@ -0,0 +1,244 @@
+use std::ops;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::math::Matrix3x3;
+
+// cone response domain of the Bradford transform
+const BRADFORD: [[f64; 3]; 3] = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+/// Reference white as a CIE 1931 xy chromaticity.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WhitePoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl WhitePoint {
+    pub const D65: WhitePoint = WhitePoint {
+        x: 0.3127,
+        y: 0.3290,
+    };
+    /// The approximately D60 white of the ACES color spaces.
+    pub const ACES: WhitePoint = WhitePoint {
+        x: 0.32168,
+        y: 0.33767,
+    };
+
+    /// CIE XYZ of the white, with a luminance of one.
+    pub fn to_xyz(&self) -> [f64; 3] {
+        chromaticity_to_xyz(self.x, self.y)
+    }
+
+    /// Bradford chromatic adaptation from this white to `target`, in XYZ.
+    pub fn adaptation_to(&self, target: WhitePoint) -> Matrix3x3 {
+        let bradford = Matrix3x3::new(BRADFORD);
+        let source = bradford * self.to_xyz();
+        let destination = bradford * target.to_xyz();
+        let scale = Matrix3x3::diagonal(
+            destination[0] / source[0],
+            destination[1] / source[1],
+            destination[2] / source[2],
+        );
+
+        bradford.inverse().unwrap() * scale * bradford
+    }
+}
+
+/// An RGB color space given by the chromaticities of its primaries and its
+/// white point. Colors are linear, transfer functions are handled separately
+/// by `TransferFunction`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorSpace {
+    pub red: (f64, f64),
+    pub green: (f64, f64),
+    pub blue: (f64, f64),
+    pub white: WhitePoint,
+}
+
+impl ColorSpace {
+    /// sRGB and Rec. 709 primaries, the space colors are rendered in.
+    pub const SRGB: ColorSpace = ColorSpace {
+        red: (0.64, 0.33),
+        green: (0.30, 0.60),
+        blue: (0.15, 0.06),
+        white: WhitePoint::D65,
+    };
+    pub const REC2020: ColorSpace = ColorSpace {
+        red: (0.708, 0.292),
+        green: (0.170, 0.797),
+        blue: (0.131, 0.046),
+        white: WhitePoint::D65,
+    };
+    pub const DISPLAY_P3: ColorSpace = ColorSpace {
+        red: (0.680, 0.320),
+        green: (0.265, 0.690),
+        blue: (0.150, 0.060),
+        white: WhitePoint::D65,
+    };
+    /// ACES AP1 primaries.
+    pub const ACES_CG: ColorSpace = ColorSpace {
+        red: (0.713, 0.293),
+        green: (0.165, 0.830),
+        blue: (0.128, 0.044),
+        white: WhitePoint::ACES,
+    };
+
+    /// Converts linear RGB in this space to CIE XYZ relative to its own
+    /// white point, `None` if the primaries are collinear.
+    pub fn to_xyz_matrix(&self) -> Option<Matrix3x3> {
+        let [xr, yr, zr] = chromaticity_to_xyz(self.red.0, self.red.1);
+        let [xg, yg, zg] = chromaticity_to_xyz(self.green.0, self.green.1);
+        let [xb, yb, zb] = chromaticity_to_xyz(self.blue.0, self.blue.1);
+        let primaries = Matrix3x3::new([[xr, xg, xb], [yr, yg, yb], [zr, zg, zb]]);
+
+        // scale the primaries so that RGB white lands on the white point
+        let [sr, sg, sb] = primaries.inverse()? * self.white.to_xyz();
+        Some(primaries * Matrix3x3::diagonal(sr, sg, sb))
+    }
+
+    pub fn from_xyz_matrix(&self) -> Option<Matrix3x3> {
+        self.to_xyz_matrix()?.inverse()
+    }
+
+    /// Converts linear RGB from this space to `target`, adapting between
+    /// white points with the Bradford transform. `None` if either space has
+    /// collinear primaries.
+    pub fn conversion_to(&self, target: ColorSpace) -> Option<Matrix3x3> {
+        let to_xyz = if self.white == target.white {
+            self.to_xyz_matrix()?
+        } else {
+            self.white.adaptation_to(target.white) * self.to_xyz_matrix()?
+        };
+
+        Some(target.from_xyz_matrix()? * to_xyz)
+    }
+
+    pub fn convert(&self, color: Color, target: ColorSpace) -> Option<Color> {
+        self.conversion_to(target)
+            .map(|conversion| conversion * color)
+    }
+}
+
+impl Canvas {
+    /// Converts linear colors from the `from` working space to `to`, e.g. a
+    /// render in sRGB primaries to a Rec. 2020 deliverable.
+    pub fn convert_color_space(&self, from: ColorSpace, to: ColorSpace) -> Option<Canvas> {
+        let conversion = from.conversion_to(to)?;
+        Some(self.map_colors(|color| conversion * color))
+    }
+}
+
+impl ops::Mul<Color> for Matrix3x3 {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Self::Output {
+        let [red, green, blue] = self * [other.red, other.green, other.blue];
+        Color::new(red, green, blue)
+    }
+}
+
+fn chromaticity_to_xyz(x: f64, y: f64) -> [f64; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_row(matrix: Matrix3x3, row: usize, expected: [f64; 3]) {
+        for (col, value) in expected.iter().enumerate() {
+            assert!(
+                (matrix[row][col] - value).abs() < 1.0e-4,
+                "{:?} differs from {:?} in row {}",
+                matrix,
+                expected,
+                row
+            );
+        }
+    }
+
+    #[test]
+    fn deriving_matrices_from_primaries() {
+        let srgb = ColorSpace::SRGB.to_xyz_matrix().unwrap();
+        assert_row(srgb, 0, [0.4124, 0.3576, 0.1805]);
+        assert_row(srgb, 1, [0.2126, 0.7152, 0.0722]);
+
+        let rec2020 = ColorSpace::REC2020.to_xyz_matrix().unwrap();
+        assert_row(rec2020, 1, [0.2627, 0.6780, 0.0593]);
+    }
+
+    #[test]
+    fn converting_between_spaces() {
+        let to_rec2020 = ColorSpace::SRGB.conversion_to(ColorSpace::REC2020).unwrap();
+        assert_row(to_rec2020, 0, [0.6274, 0.3293, 0.0433]);
+
+        let to_p3 = ColorSpace::SRGB
+            .conversion_to(ColorSpace::DISPLAY_P3)
+            .unwrap();
+        assert_row(to_p3, 0, [0.8225, 0.1775, 0.0]);
+
+        // saturated Rec. 2020 green lies outside the sRGB gamut
+        let green = ColorSpace::REC2020
+            .convert(Color::new(0.0, 1.0, 0.0), ColorSpace::SRGB)
+            .unwrap();
+        assert!(green.red < 0.0 && green.blue < 0.0);
+    }
+
+    #[test]
+    fn adapting_white_points() {
+        let to_aces = ColorSpace::SRGB.conversion_to(ColorSpace::ACES_CG).unwrap();
+        assert_row(to_aces, 0, [0.6131, 0.3395, 0.0474]);
+
+        let white = ColorSpace::SRGB.convert(Color::new(1.0, 1.0, 1.0), ColorSpace::ACES_CG);
+        assert_eq!(white, Some(Color::new(1.0, 1.0, 1.0)));
+
+        let identity = WhitePoint::D65.adaptation_to(WhitePoint::D65);
+        assert_row(identity, 0, [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn converting_back_and_forth() {
+        let color = Color::new(0.2, 0.5, 0.9);
+        let there = ColorSpace::DISPLAY_P3
+            .convert(color, ColorSpace::ACES_CG)
+            .unwrap();
+        let back = ColorSpace::ACES_CG.convert(there, ColorSpace::DISPLAY_P3);
+
+        assert_eq!(back, Some(color));
+    }
+
+    #[test]
+    fn collinear_primaries_have_no_matrix() {
+        let degenerate = ColorSpace {
+            green: (0.395, 0.195),
+            ..ColorSpace::SRGB
+        };
+
+        assert_eq!(degenerate.to_xyz_matrix(), None);
+        assert_eq!(ColorSpace::SRGB.conversion_to(degenerate), None);
+    }
+
+    #[test]
+    fn converting_working_spaces() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 1.0, 1.0));
+        c.write_pixel(1, 0, Color::new(1.0, 0.0, 0.0));
+
+        let converted = c
+            .convert_color_space(ColorSpace::SRGB, ColorSpace::REC2020)
+            .unwrap();
+        assert_eq!(converted.pixel_at(0, 0), Color::new(1.0, 1.0, 1.0));
+        let red = converted.pixel_at(1, 0);
+        assert!(red.red < 1.0 && red.green > 0.0);
+
+        let back = converted
+            .convert_color_space(ColorSpace::REC2020, ColorSpace::SRGB)
+            .unwrap();
+        assert_eq!(back.pixel_at(1, 0), Color::new(1.0, 0.0, 0.0));
+    }
+}
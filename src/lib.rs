@@ -2,5 +2,13 @@ mod canvas;
 mod color;
 pub mod math;
 
-pub use self::canvas::Canvas;
-pub use self::color::Color;
+pub use self::canvas::{
+    Canvas, ContactSheet, EdgeMode, Exposure, ExrCompression, ExrPixelType, ExrWriter, HdrError,
+    Histogram, Kernel, Pixel, Quantization, ResizeFilter, Rgb32, Rgba, TgaError, Tile,
+    ToneMapOperator,
+};
+pub use self::color::{
+    BlackbodyNormalization, Color, ColorRamp, ColorSpace, HeroWavelengths, ParseColorError,
+    RampMode, RampSpace, Spectrum, TransferFunction, WhitePoint, HERO_WAVELENGTHS, MAX_WAVELENGTH,
+    MIN_WAVELENGTH,
+};
@@ -16,7 +16,7 @@ fn main() {
         let x_pos = (point.x * radius + 250.0) as usize;
         let y_pos = (point.z * radius + 250.0) as usize;
         if hour % 5 == 0 {
-            canvas.write_pixel(x_pos, y_pos, Color::new(1.0, 0.0, 0.0));
+            canvas.fill_circle(x_pos as isize, y_pos as isize, 3, Color::new(1.0, 0.0, 0.0));
         } else {
             canvas.write_pixel(x_pos, y_pos, Color::new(1.0, 1.0, 1.0));
         }
@@ -27,12 +27,12 @@ fn main() {
     let mut data = canvas.to_tga();
 
     let mut file = match File::create("output.tga") {
-        Err(why) => panic!("couldn't create output.tga: {}", why.to_string()),
+        Err(why) => panic!("couldn't create output.tga: {}", why),
         Ok(file) => file,
     };
 
     match file.write_all(data.as_mut()) {
-        Err(why) => panic!("couldn't write to output.tga: {}", why.to_string()),
+        Err(why) => panic!("couldn't write to output.tga: {}", why),
         Ok(_) => println!("successfully wrote to output.tga"),
     }
 }
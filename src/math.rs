@@ -25,10 +25,16 @@ macro_rules! assert_approx_4_by_4_eq {
 
 mod matrix;
 mod point;
+mod quaternion;
+mod ray;
+mod transform;
 mod tuple;
 mod vector;
 
-pub use self::matrix::{Matrix2x2, Matrix3x3, Matrix4x4};
+pub use self::matrix::{Matrix, Matrix2x2, Matrix3x3, Matrix4x4};
 pub use self::point::Point;
+pub use self::quaternion::Quaternion;
+pub use self::ray::Ray;
+pub use self::transform::Transform;
 pub use self::tuple::Tuple;
 pub use self::vector::Vector;
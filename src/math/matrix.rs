@@ -6,33 +6,213 @@ use crate::math::Point;
 use crate::math::Tuple;
 use crate::math::Vector;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub struct Matrix4x4 {
-    data: [[f64; 4]; 4],
-}
+// pivots smaller than this, once rows and columns are scaled to a largest
+// entry of about one, count as zero
+const SINGULARITY_TOLERANCE: f64 = 1.0e-12;
 
+/// Row-major matrix with `R` rows and `C` columns.
 #[derive(Copy, Clone, Debug, PartialEq)]
-pub struct Matrix3x3 {
-    data: [[f64; 3]; 3],
+pub struct Matrix<const R: usize, const C: usize> {
+    data: [[f64; C]; R],
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub struct Matrix2x2 {
-    data: [[f64; 2]; 2],
+pub type Matrix4x4 = Matrix<4, 4>;
+pub type Matrix3x3 = Matrix<3, 3>;
+pub type Matrix2x2 = Matrix<2, 2>;
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    pub fn new(data: [[f64; C]; R]) -> Matrix<R, C> {
+        Matrix { data }
+    }
+
+    pub fn zero() -> Matrix<R, C> {
+        Matrix {
+            data: [[0.0; C]; R],
+        }
+    }
+
+    pub fn transpose(&self) -> Matrix<C, R> {
+        let mut result = Matrix::zero();
+
+        for row in 0..R {
+            for col in 0..C {
+                result[col][row] = self[row][col];
+            }
+        }
+
+        result
+    }
 }
 
-impl Matrix4x4 {
-    pub fn identity() -> Matrix4x4 {
-        Matrix4x4 {
-            data: [
-                [1.0, 0.0, 0.0, 0.0],
-                [0.0, 1.0, 0.0, 0.0],
-                [0.0, 0.0, 1.0, 0.0],
-                [0.0, 0.0, 0.0, 1.0],
-            ],
+impl<const N: usize> Matrix<N, N> {
+    pub fn identity() -> Matrix<N, N> {
+        let mut result = Matrix::zero();
+        for i in 0..N {
+            result[i][i] = 1.0;
+        }
+        result
+    }
+
+    /// Determinant by elimination with partial pivoting.
+    pub fn determinant(&self) -> f64 {
+        // a zero column would otherwise hide a NaN elsewhere
+        if self.data.iter().flatten().any(|value| value.is_nan()) {
+            return f64::NAN;
+        }
+        let mut data = self.data;
+        let mut det = 1.0;
+
+        for col in 0..N {
+            let pivot = pivot_row(&data, col);
+            if data[pivot][col] == 0.0 {
+                return 0.0;
+            }
+            if pivot != col {
+                data.swap(col, pivot);
+                det = -det;
+            }
+            det *= data[col][col];
+
+            let pivot_values = data[col];
+            for values in data.iter_mut().skip(col + 1) {
+                let factor = values[col] / pivot_values[col];
+                for (value, pivot_value) in values.iter_mut().zip(&pivot_values).skip(col) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+
+        det
+    }
+
+    pub fn is_invertiable(&self) -> bool {
+        self.inverse().is_some()
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting. Columns and then rows
+    /// are first scaled by powers of two to a largest entry of about one, so
+    /// a large entry like a translation doesn't make the rest look negligible.
+    /// Returns `None` when a scaled pivot is negligible.
+    pub fn inverse(&self) -> Option<Matrix<N, N>> {
+        if !self.data.iter().flatten().all(|value| value.is_finite()) {
+            return None;
+        }
+
+        let mut left = self.data;
+        let mut column_scales = [1.0; N];
+        for (col, column_scale) in column_scales.iter_mut().enumerate() {
+            *column_scale = power_of_two_scale(left.iter().map(|row| row[col]))?;
+            for row in left.iter_mut() {
+                row[col] *= *column_scale;
+            }
         }
+        let mut row_scales = [1.0; N];
+        for (row, row_scale) in left.iter_mut().zip(row_scales.iter_mut()) {
+            *row_scale = power_of_two_scale(row.iter().copied())?;
+            for value in row.iter_mut() {
+                *value *= *row_scale;
+            }
+        }
+
+        let mut right = Matrix::<N, N>::identity().data;
+
+        for col in 0..N {
+            let pivot = pivot_row(&left, col);
+            if left[pivot][col].abs() <= SINGULARITY_TOLERANCE {
+                return None;
+            }
+            left.swap(col, pivot);
+            right.swap(col, pivot);
+
+            let divisor = left[col][col];
+            for k in 0..N {
+                left[col][k] /= divisor;
+                right[col][k] /= divisor;
+            }
+
+            for row in (0..N).filter(|&row| row != col) {
+                let factor = left[row][col];
+                for k in 0..N {
+                    left[row][k] -= factor * left[col][k];
+                    right[row][k] -= factor * right[col][k];
+                }
+            }
+        }
+
+        // the scaled matrix is R * A * C, so the inverse of A is C * inverse * R
+        for (row, column_scale) in right.iter_mut().zip(column_scales.iter()) {
+            for (value, row_scale) in row.iter_mut().zip(row_scales.iter()) {
+                *value *= column_scale * row_scale;
+            }
+        }
+
+        Some(Matrix { data: right })
+    }
+}
+
+// row at or below col with the largest magnitude in that column
+fn pivot_row<const N: usize>(data: &[[f64; N]; N], col: usize) -> usize {
+    (col..N)
+        .max_by(|&a, &b| data[a][col].abs().total_cmp(&data[b][col].abs()))
+        .unwrap()
+}
+
+// power of two that brings the largest magnitude close to one, `None` if all
+// values are zero or the scale overflows
+fn power_of_two_scale<I: Iterator<Item = f64>>(values: I) -> Option<f64> {
+    let largest = values.fold(0.0_f64, |largest, value| largest.max(value.abs()));
+    if largest == 0.0 {
+        return None;
     }
+    let scale = 2.0_f64.powi(-largest.log2().round() as i32);
+    if scale.is_finite() {
+        Some(scale)
+    } else {
+        None
+    }
+}
 
+// submatrices shrink the size, which const generics can't express yet
+macro_rules! impl_submatrix {
+    ($n:expr, $m:expr) => {
+        impl Matrix<$n, $n> {
+            pub fn submatrix(
+                &self,
+                row_to_remove: usize,
+                column_to_remove: usize,
+            ) -> Matrix<$m, $m> {
+                let mut result = Matrix::zero();
+                let rows = (0..$n).filter(|&row| row != row_to_remove);
+
+                for (target_row, row) in rows.enumerate() {
+                    let cols = (0..$n).filter(|&col| col != column_to_remove);
+                    for (target_col, col) in cols.enumerate() {
+                        result[target_row][target_col] = self[row][col];
+                    }
+                }
+
+                result
+            }
+
+            pub fn minor(&self, row: usize, col: usize) -> f64 {
+                self.submatrix(row, col).determinant()
+            }
+
+            pub fn cofactor(&self, row: usize, col: usize) -> f64 {
+                if (row + col).is_multiple_of(2) {
+                    self.minor(row, col)
+                } else {
+                    -self.minor(row, col)
+                }
+            }
+        }
+    };
+}
+
+impl_submatrix!(4, 3);
+impl_submatrix!(3, 2);
+
+impl Matrix4x4 {
     pub fn translation(x: f64, y: f64, z: f64) -> Matrix4x4 {
         Matrix4x4 {
             data: [
@@ -99,91 +279,6 @@ impl Matrix4x4 {
         }
     }
 
-    pub fn transpose(&self) -> Matrix4x4 {
-        let mut result = Matrix4x4::identity();
-
-        for row in 0..=3 {
-            result[0][row] = self[row][0];
-            result[1][row] = self[row][1];
-            result[2][row] = self[row][2];
-            result[3][row] = self[row][3];
-        }
-
-        result
-    }
-
-    pub fn submatrix(&self, row_to_remove: usize, column_to_remove: usize) -> Matrix3x3 {
-        // TODO: figure out how this generic stuff works an then extract this!
-        let vec: Vec<Vec<f64>> = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|&(i, _)| i != row_to_remove)
-            .map(|(_, e)| {
-                e.iter()
-                    .enumerate()
-                    .filter(|&(i, _)| i != column_to_remove)
-                    .map(|(_, e)| *e)
-                    .collect()
-            })
-            .collect();
-
-        Matrix3x3 {
-            data: [
-                [vec[0][0], vec[0][1], vec[0][2]],
-                [vec[1][0], vec[1][1], vec[1][2]],
-                [vec[2][0], vec[2][1], vec[2][2]],
-            ],
-        }
-    }
-
-    pub fn minor(&self, row: usize, col: usize) -> f64 {
-        self.submatrix(row, col).determinant()
-    }
-
-    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        if (row + col) % 2 == 0 {
-            self.minor(row, col)
-        } else {
-            -self.minor(row, col)
-        }
-    }
-
-    pub fn determinant(&self) -> f64 {
-        let mut det: f64 = 0.0;
-        for col in 0..=3 {
-            det += self[0][col] * self.cofactor(0, col)
-        }
-        det
-    }
-
-    pub fn is_invertiable(&self) -> bool {
-        self.determinant() != 0.0
-    }
-
-    pub fn inverse(&self) -> Option<Matrix4x4> {
-        let mut data: [[f64; 4]; 4] = [[0.0; 4]; 4];
-        let det = self.determinant();
-
-        if det == 0.0 { return None; }
-
-        for row in 0..=3 {
-            for col in 0..=3 {
-                let c = self.cofactor(row, col);
-                data[col][row] = c / det;
-            }
-        }
-
-        Some(Matrix4x4 {
-            data: [
-                [data[0][0], data[0][1], data[0][2], data[0][3]],
-                [data[1][0], data[1][1], data[1][2], data[1][3]],
-                [data[2][0], data[2][1], data[2][2], data[2][3]],
-                [data[3][0], data[3][1], data[3][2], data[3][3]],
-            ],
-        })
-    }
-
     pub fn translate(&self, x: f64, y: f64, z: f64) -> Matrix4x4 {
         Matrix4x4::translation(x, y, z) * *self
     }
@@ -210,97 +305,51 @@ impl Matrix4x4 {
 }
 
 impl Matrix3x3 {
-    pub fn submatrix(&self, row_to_remove: usize, column_to_remove: usize) -> Matrix2x2 {
-        // TODO: figure out how this generic stuff works an then extract this!
-        let vec: Vec<Vec<f64>> = self
-            .data
-            .iter()
-            .enumerate()
-            .filter(|&(i, _)| i != row_to_remove)
-            .map(|(_, e)| {
-                e.iter()
-                    .enumerate()
-                    .filter(|&(i, _)| i != column_to_remove)
-                    .map(|(_, e)| *e)
-                    .collect()
-            })
-            .collect();
-
-        Matrix2x2 {
-            data: [[vec[0][0], vec[0][1]], [vec[1][0], vec[1][1]]],
-        }
-    }
-
-    pub fn minor(&self, row: usize, col: usize) -> f64 {
-        self.submatrix(row, col).determinant()
-    }
-
-    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        if (row + col) % 2 == 0 {
-            self.minor(row, col)
-        } else {
-            -self.minor(row, col)
-        }
-    }
-
-    pub fn determinant(&self) -> f64 {
-        let mut det: f64 = 0.0;
-        for col in 0..=2 {
-            det += self[0][col] * self.cofactor(0, col)
+    pub fn diagonal(x: f64, y: f64, z: f64) -> Matrix3x3 {
+        Matrix {
+            data: [[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]],
         }
-        det
-    }
-}
-
-impl Matrix2x2 {
-    pub fn determinant(&self) -> f64 {
-        self[0][0] * self[1][1] - self[0][1] * self[1][0]
     }
 }
 
-impl Index<usize> for Matrix4x4 {
-    type Output = [f64; 4];
+impl<const R: usize, const C: usize> Index<usize> for Matrix<R, C> {
+    type Output = [f64; C];
 
     fn index(&self, other: usize) -> &Self::Output {
         &self.data[other]
     }
 }
 
-impl IndexMut<usize> for Matrix4x4 {
+impl<const R: usize, const C: usize> IndexMut<usize> for Matrix<R, C> {
     fn index_mut(&mut self, other: usize) -> &mut Self::Output {
         &mut self.data[other]
     }
 }
 
-impl Index<usize> for Matrix3x3 {
-    type Output = [f64; 3];
+impl<const R: usize, const K: usize, const C: usize> Mul<Matrix<K, C>> for Matrix<R, K> {
+    type Output = Matrix<R, C>;
 
-    fn index(&self, other: usize) -> &Self::Output {
-        &self.data[other]
-    }
-}
+    fn mul(self, other: Matrix<K, C>) -> Self::Output {
+        let mut result = Matrix::zero();
 
-impl Index<usize> for Matrix2x2 {
-    type Output = [f64; 2];
+        for row in 0..R {
+            for col in 0..C {
+                result[row][col] = (0..K).map(|k| self[row][k] * other[k][col]).sum();
+            }
+        }
 
-    fn index(&self, other: usize) -> &Self::Output {
-        &self.data[other]
+        result
     }
 }
 
-impl Mul<Matrix4x4> for Matrix4x4 {
-    type Output = Self;
+impl<const R: usize, const C: usize> Mul<[f64; C]> for Matrix<R, C> {
+    type Output = [f64; R];
 
-    fn mul(self, other: Self) -> Self::Output {
-        let mut result = Matrix4x4::identity();
+    fn mul(self, other: [f64; C]) -> Self::Output {
+        let mut result = [0.0; R];
 
-        for row in 0..=3 {
-            for col in 0..=3 {
-                result[row][col] = self[row][0] * other[0][col]
-                    + self[row][1] * other[1][col]
-                    + self[row][2] * other[2][col]
-                    + self[row][3] * other[3][col]
-            }
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = (0..C).map(|col| self[row][col] * other[col]).sum();
         }
 
         result
@@ -543,6 +592,16 @@ mod test {
         assert_approx_eq!(mat.determinant(), -4071.0);
     }
 
+    #[test]
+    fn determinant_with_nan_entries_is_nan() {
+        assert!(Matrix4x4::scaling(f64::NAN, 1.0, 1.0).determinant().is_nan());
+        assert!(Matrix3x3::diagonal(1.0, 0.0, f64::NAN).determinant().is_nan());
+
+        let mut mat = Matrix2x2::zero();
+        mat[0][1] = f64::NAN;
+        assert!(mat.determinant().is_nan());
+    }
+
     #[test]
     fn test_4_by_4_is_invertiable() {
         let mat = Matrix4x4 {
@@ -554,7 +613,7 @@ mod test {
             ]
         };
         assert_approx_eq!(mat.determinant(), -2120.0);
-        assert_eq!(mat.is_invertiable(), true)
+        assert!(mat.is_invertiable())
     }
 
     #[test]
@@ -568,7 +627,7 @@ mod test {
             ]
         };
         assert_approx_eq!(mat.determinant(), 0.0);
-        assert_eq!(mat.is_invertiable(), false)
+        assert!(!mat.is_invertiable())
     }
 
     #[test]
@@ -811,4 +870,97 @@ mod test {
 
         assert_eq!(transform * p, Point::new(15.0, 0.0, 7.0))
     }
+
+    #[test]
+    fn inverse_of_a_3_by_3_matrix() {
+        let mat = Matrix3x3::new([[2.0, 0.0, 1.0], [1.0, 3.0, 2.0], [1.0, 1.0, 2.0]]);
+        let inverse = mat.inverse().unwrap();
+        let product = mat * inverse;
+
+        assert_approx_eq!(inverse[0][0], 4.0 / 6.0);
+        for row in 0..=2 {
+            for col in 0..=2 {
+                assert_approx_eq!(product[row][col], Matrix3x3::identity()[row][col]);
+            }
+        }
+        assert_eq!(Matrix3x3::diagonal(1.0, 0.0, 1.0).inverse(), None);
+        assert_eq!(mat.transpose()[0][1], 1.0);
+    }
+
+    #[test]
+    fn near_singular_matrices_are_not_invertible() {
+        // two rows that only differ far below the size of their entries
+        let dependent = Matrix4x4::new([
+            [1.0, 1.0, 0.0, 0.0],
+            [1.0, 1.0 + 1.0e-14, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert_eq!(dependent.inverse(), None);
+        assert!(!dependent.is_invertiable());
+
+        // rounding can keep the determinant of a rotated flat scale off zero
+        let flattened = Matrix4x4::scaling(1.0, 0.0, 1.0)
+            .rotate_x(0.3)
+            .rotate_z(1.1);
+        assert!(!flattened.is_invertiable());
+
+        let large = Matrix4x4::scaling(1.0e8, 1.0e8, 1.0e8);
+        assert_approx_4_by_4_eq!(
+            large.inverse().unwrap(),
+            Matrix4x4::scaling(1.0e-8, 1.0e-8, 1.0e-8)
+        );
+
+        let broken = Matrix4x4::scaling(f64::NAN, 1.0, 1.0);
+        assert_eq!(broken.inverse(), None);
+    }
+
+    #[test]
+    fn large_translations_stay_invertible() {
+        let far = Matrix4x4::translation(1.0e12, 0.0, 0.0);
+        assert_eq!(far.inverse(), Some(Matrix4x4::translation(-1.0e12, 0.0, 0.0)));
+
+        let small = Matrix4x4::scaling(0.01, 0.01, 0.01).translate(1.0e10, 0.0, 0.0);
+        let inverse = small.inverse().unwrap();
+        assert_approx_eq!(inverse[0][0], 100.0);
+        assert!((inverse[0][3] / -1.0e12 - 1.0).abs() < 1.0e-12);
+
+        // a uniformly tiny scale is still a well conditioned matrix
+        let squashed = Matrix4x4::scaling(1.0e-14, 1.0e-14, 1.0e-14);
+        assert!(squashed.is_invertiable());
+    }
+
+    #[test]
+    fn inverting_requires_pivoting() {
+        // the leading zero breaks elimination without row swaps
+        let mat = Matrix4x4::rotation_z(std::f64::consts::PI / 2.0).translate(1.0, 2.0, 3.0);
+        let product = mat * mat.inverse().unwrap();
+
+        assert_approx_4_by_4_eq!(product, Matrix4x4::identity());
+    }
+
+    #[test]
+    fn multiplying_matrices_of_different_shapes() {
+        let a = Matrix::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let b = a.transpose();
+
+        assert_eq!(b, Matrix::new([[1.0, 4.0], [2.0, 5.0], [3.0, 6.0]]));
+        assert_eq!(a * b, Matrix2x2::new([[14.0, 32.0], [32.0, 77.0]]));
+        assert_eq!(a * [1.0, 0.0, -1.0], [-2.0, -2.0]);
+    }
+
+    #[test]
+    fn transforming_uv_coordinates() {
+        // scale by 2 around the texture center, in homogeneous 2D coordinates
+        let mut to_center = Matrix3x3::identity();
+        to_center[0][2] = -0.5;
+        to_center[1][2] = -0.5;
+        let back = to_center.inverse().unwrap();
+        let transform = back * Matrix3x3::diagonal(2.0, 2.0, 1.0) * to_center;
+
+        assert_eq!(transform * [0.75, 0.5, 1.0], [1.0, 0.5, 1.0]);
+        let mut scale = Matrix2x2::identity();
+        scale[1][1] = 4.0;
+        assert_eq!(scale.inverse().unwrap()[1][1], 0.25);
+    }
 }
@@ -0,0 +1,308 @@
+use std::ops::{Mul, Neg};
+
+use crate::math::Matrix4x4;
+use crate::math::Point;
+use crate::math::Vector;
+
+// below this angle between rotations slerp falls back to nlerp
+const SLERP_THRESHOLD: f64 = 1.0e-6;
+
+/// Rotation as a quaternion `w + xi + yj + zk`. Rotations expect unit
+/// quaternions, products of unit quaternions stay normalized up to rounding.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Quaternion {
+        Quaternion { w, x, y, z }
+    }
+
+    pub fn identity() -> Quaternion {
+        Quaternion::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Rotation by `angle` radians around `axis`, following the same
+    /// handedness as `Matrix4x4::rotation_x/y/z`.
+    pub fn from_axis_angle(axis: Vector, angle: f64) -> Quaternion {
+        let axis = axis.normalize();
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Quaternion::new(cos, axis.x * sin, axis.y * sin, axis.z * sin)
+    }
+
+    /// Rotates around x first, then y, then z, like
+    /// `Matrix4x4::identity().rotate_x(x).rotate_y(y).rotate_z(z)`.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Quaternion {
+        Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), z)
+            * Quaternion::from_axis_angle(Vector::new(0.0, 1.0, 0.0), y)
+            * Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), x)
+    }
+
+    /// Extracts the rotation of a matrix without scaling or skewing.
+    /// Translation is ignored.
+    pub fn from_matrix(m: &Matrix4x4) -> Quaternion {
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // pivot on the largest component to stay away from tiny divisors
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quaternion::new(
+                s / 4.0,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quaternion::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quaternion::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quaternion::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+            )
+        };
+
+        q.normalize()
+    }
+
+    pub fn to_matrix(&self) -> Matrix4x4 {
+        let Quaternion { w, x, y, z } = *self;
+        let mut result = Matrix4x4::identity();
+
+        result[0][0] = 1.0 - 2.0 * (y * y + z * z);
+        result[0][1] = 2.0 * (x * y - w * z);
+        result[0][2] = 2.0 * (x * z + w * y);
+        result[1][0] = 2.0 * (x * y + w * z);
+        result[1][1] = 1.0 - 2.0 * (x * x + z * z);
+        result[1][2] = 2.0 * (y * z - w * x);
+        result[2][0] = 2.0 * (x * z - w * y);
+        result[2][1] = 2.0 * (y * z + w * x);
+        result[2][2] = 1.0 - 2.0 * (x * x + y * y);
+
+        result
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Quaternion {
+        let magnitude = self.magnitude();
+        Quaternion::new(
+            self.w / magnitude,
+            self.x / magnitude,
+            self.y / magnitude,
+            self.z / magnitude,
+        )
+    }
+
+    /// The inverse rotation of a unit quaternion.
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Normalized linear interpolation. Cheaper than `slerp` but does not
+    /// move at constant angular speed.
+    pub fn nlerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let other = self.shorter_path_to(other);
+        Quaternion::new(
+            self.w + (other.w - self.w) * t,
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+            self.z + (other.z - self.z) * t,
+        )
+        .normalize()
+    }
+
+    /// Spherical linear interpolation along the shorter arc between two
+    /// unit quaternions.
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Quaternion {
+        let other = self.shorter_path_to(other);
+        let angle = self.dot(&other).clamp(-1.0, 1.0).acos();
+        if angle < SLERP_THRESHOLD {
+            return self.nlerp(&other, t);
+        }
+
+        let from = ((1.0 - t) * angle).sin() / angle.sin();
+        let to = (t * angle).sin() / angle.sin();
+        Quaternion::new(
+            self.w * from + other.w * to,
+            self.x * from + other.x * to,
+            self.y * from + other.y * to,
+            self.z * from + other.z * to,
+        )
+    }
+
+    // q and -q are the same rotation, pick the one closer to self
+    fn shorter_path_to(&self, other: &Quaternion) -> Quaternion {
+        if self.dot(other) < 0.0 {
+            -*other
+        } else {
+            *other
+        }
+    }
+}
+
+impl Mul<Quaternion> for Quaternion {
+    type Output = Self;
+
+    // a * b rotates by b first and then by a
+    fn mul(self, other: Self) -> Self::Output {
+        Quaternion::new(
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        )
+    }
+}
+
+impl Mul<Vector> for Quaternion {
+    type Output = Vector;
+
+    fn mul(self, other: Self::Output) -> Self::Output {
+        // v + 2w(u x v) + 2u x (u x v), avoiding the full sandwich product
+        let u = Vector::new(self.x, self.y, self.z);
+        let t = u.cross(other) * 2.0;
+        other + t * self.w + u.cross(t)
+    }
+}
+
+impl Mul<Point> for Quaternion {
+    type Output = Point;
+
+    fn mul(self, other: Self::Output) -> Self::Output {
+        let rotated = self * Vector::new(other.x, other.y, other.z);
+        Point::new(rotated.x, rotated.y, rotated.z)
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Quaternion::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn rotating_around_an_axis() {
+        let half_quarter = Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI / 4.0);
+        let p = Point::new(0.0, 1.0, 0.0);
+
+        assert_eq!(
+            half_quarter * p,
+            Point::new(0.0, 2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0)
+        );
+        assert_eq!(half_quarter * p, Matrix4x4::rotation_x(PI / 4.0) * p);
+
+        let full_quarter = Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 2.0), PI / 2.0);
+        let v = full_quarter * Vector::new(0.0, 1.0, 0.0);
+        assert_approx_eq!(v.x, -1.0);
+        assert_approx_eq!(v.y, 0.0);
+    }
+
+    #[test]
+    fn euler_angles_match_chained_rotations() {
+        let (x, y, z) = (0.3, -1.2, 2.0);
+        let q = Quaternion::from_euler(x, y, z);
+        let m = Matrix4x4::identity().rotate_x(x).rotate_y(y).rotate_z(z);
+
+        assert_approx_4_by_4_eq!(q.to_matrix(), m);
+        let p = Point::new(1.0, -2.0, 3.0);
+        assert_eq!(q * p, m * p);
+    }
+
+    #[test]
+    fn multiplying_composes_rotations() {
+        let a = Quaternion::from_axis_angle(Vector::new(0.0, 1.0, 0.0), 0.7);
+        let b = Quaternion::from_axis_angle(Vector::new(1.0, 1.0, 0.0), -1.1);
+
+        assert_approx_4_by_4_eq!((a * b).to_matrix(), a.to_matrix() * b.to_matrix());
+        assert_approx_eq!((a * a.conjugate()).w, 1.0);
+        assert_approx_eq!((a * b).magnitude(), 1.0);
+    }
+
+    #[test]
+    fn converting_from_matrices() {
+        let rotations = [
+            Quaternion::from_euler(0.1, 0.2, 0.3),
+            Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI),
+            Quaternion::from_axis_angle(Vector::new(0.0, 1.0, 0.0), 3.0),
+            Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), -3.0),
+        ];
+
+        for q in rotations.iter() {
+            let m = q.to_matrix().translate(1.0, 2.0, 3.0);
+            let back = Quaternion::from_matrix(&m);
+            assert_approx_eq!(back.dot(q).abs(), 1.0);
+        }
+    }
+
+    #[test]
+    fn normalizing_a_quaternion() {
+        let q = Quaternion::new(1.0, 2.0, 2.0, 4.0).normalize();
+
+        assert_eq!(q, Quaternion::new(0.2, 0.4, 0.4, 0.8));
+        assert_approx_eq!(q.magnitude(), 1.0);
+    }
+
+    #[test]
+    fn interpolating_rotations() {
+        let axis = Vector::new(0.0, 0.0, 1.0);
+        let from = Quaternion::identity();
+        let to = Quaternion::from_axis_angle(axis, PI / 2.0);
+        let halfway = Quaternion::from_axis_angle(axis, PI / 4.0);
+
+        let slerped = from.slerp(&to, 0.5);
+        assert_approx_eq!(slerped.dot(&halfway), 1.0);
+        let third = from.slerp(&to, 1.0 / 3.0);
+        assert_approx_eq!(third.dot(&Quaternion::from_axis_angle(axis, PI / 6.0)), 1.0);
+
+        let nlerped = from.nlerp(&to, 0.5);
+        assert_approx_eq!(nlerped.dot(&halfway), 1.0);
+        assert_approx_eq!(from.nlerp(&to, 0.3).magnitude(), 1.0);
+    }
+
+    #[test]
+    fn interpolating_takes_the_shorter_arc() {
+        let from = Quaternion::identity();
+        // the same rotation as a quarter turn, on the far side of the sphere
+        let to = -Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), PI / 2.0);
+        let halfway = from.slerp(&to, 0.5);
+
+        let v = halfway * Vector::new(1.0, 0.0, 0.0);
+        assert_approx_eq!(v.x, 2.0_f64.sqrt() / 2.0);
+        assert_approx_eq!(v.y, 2.0_f64.sqrt() / 2.0);
+        assert_eq!(from.slerp(&from, 0.5), from);
+    }
+}
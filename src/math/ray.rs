@@ -0,0 +1,34 @@
+use crate::math::Point;
+use crate::math::Vector;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: Vector,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: Vector) -> Ray {
+        Ray { origin, direction }
+    }
+
+    /// Point at distance `t` along the ray, in multiples of the direction.
+    pub fn position(&self, t: f64) -> Point {
+        self.origin + self.direction * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computing_a_point_from_a_distance() {
+        let r = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+
+        assert_eq!(r.position(0.0), Point::new(2.0, 3.0, 4.0));
+        assert_eq!(r.position(1.0), Point::new(3.0, 3.0, 4.0));
+        assert_eq!(r.position(-1.0), Point::new(1.0, 3.0, 4.0));
+        assert_eq!(r.position(2.5), Point::new(4.5, 3.0, 4.0));
+    }
+}
@@ -0,0 +1,233 @@
+use std::ops::Mul;
+
+use crate::math::Matrix4x4;
+use crate::math::Point;
+use crate::math::Quaternion;
+use crate::math::Ray;
+use crate::math::Vector;
+
+/// A `Matrix4x4` together with its inverse and inverse-transpose, so that
+/// transforming rays and normals never has to invert a matrix.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    matrix: Matrix4x4,
+    inverse: Matrix4x4,
+    inverse_transpose: Matrix4x4,
+}
+
+impl Transform {
+    /// Inverts `matrix` once up front, `None` if it is not invertible.
+    pub fn new(matrix: Matrix4x4) -> Option<Transform> {
+        matrix
+            .inverse()
+            .map(|inverse| Transform::from_parts(matrix, inverse))
+    }
+
+    pub fn identity() -> Transform {
+        Transform::from_parts(Matrix4x4::identity(), Matrix4x4::identity())
+    }
+
+    pub fn translation(x: f64, y: f64, z: f64) -> Transform {
+        Transform::from_parts(
+            Matrix4x4::translation(x, y, z),
+            Matrix4x4::translation(-x, -y, -z),
+        )
+    }
+
+    pub fn scaling(x: f64, y: f64, z: f64) -> Transform {
+        assert!(x != 0.0 && y != 0.0 && z != 0.0); // TODO: real error handling
+        Transform::from_parts(
+            Matrix4x4::scaling(x, y, z),
+            Matrix4x4::scaling(1.0 / x, 1.0 / y, 1.0 / z),
+        )
+    }
+
+    pub fn rotation_x(r: f64) -> Transform {
+        Transform::from_parts(Matrix4x4::rotation_x(r), Matrix4x4::rotation_x(-r))
+    }
+
+    pub fn rotation_y(r: f64) -> Transform {
+        Transform::from_parts(Matrix4x4::rotation_y(r), Matrix4x4::rotation_y(-r))
+    }
+
+    pub fn rotation_z(r: f64) -> Transform {
+        Transform::from_parts(Matrix4x4::rotation_z(r), Matrix4x4::rotation_z(-r))
+    }
+
+    pub fn rotation(q: Quaternion) -> Transform {
+        let q = q.normalize();
+        Transform::from_parts(q.to_matrix(), q.conjugate().to_matrix())
+    }
+
+    // the caller guarantees that inverse really is the inverse of matrix
+    fn from_parts(matrix: Matrix4x4, inverse: Matrix4x4) -> Transform {
+        Transform {
+            matrix,
+            inverse,
+            inverse_transpose: inverse.transpose(),
+        }
+    }
+
+    pub fn matrix(&self) -> &Matrix4x4 {
+        &self.matrix
+    }
+
+    pub fn inverse_matrix(&self) -> &Matrix4x4 {
+        &self.inverse
+    }
+
+    pub fn inverse_transpose(&self) -> &Matrix4x4 {
+        &self.inverse_transpose
+    }
+
+    /// The inverse transform, without inverting anything.
+    pub fn inverse(&self) -> Transform {
+        Transform::from_parts(self.inverse, self.matrix)
+    }
+
+    /// Applies `other` after this transform.
+    pub fn then(&self, other: Transform) -> Transform {
+        other * *self
+    }
+
+    pub fn translate(&self, x: f64, y: f64, z: f64) -> Transform {
+        self.then(Transform::translation(x, y, z))
+    }
+
+    pub fn scale(&self, x: f64, y: f64, z: f64) -> Transform {
+        self.then(Transform::scaling(x, y, z))
+    }
+
+    pub fn rotate_x(&self, r: f64) -> Transform {
+        self.then(Transform::rotation_x(r))
+    }
+
+    pub fn rotate_y(&self, r: f64) -> Transform {
+        self.then(Transform::rotation_y(r))
+    }
+
+    pub fn rotate_z(&self, r: f64) -> Transform {
+        self.then(Transform::rotation_z(r))
+    }
+
+    pub fn rotate(&self, q: Quaternion) -> Transform {
+        self.then(Transform::rotation(q))
+    }
+
+    pub fn transform_point(&self, p: Point) -> Point {
+        self.matrix * p
+    }
+
+    pub fn transform_vector(&self, v: Vector) -> Vector {
+        self.matrix * v
+    }
+
+    /// Transforms a surface normal with the inverse-transpose so that it
+    /// stays perpendicular to the surface. The result is not normalized.
+    pub fn transform_normal(&self, n: Vector) -> Vector {
+        self.inverse_transpose * n
+    }
+
+    pub fn transform_ray(&self, ray: Ray) -> Ray {
+        Ray::new(
+            self.transform_point(ray.origin),
+            self.transform_vector(ray.direction),
+        )
+    }
+}
+
+impl Mul<Transform> for Transform {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Transform::from_parts(self.matrix * other.matrix, other.inverse * self.inverse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn caching_the_inverse() {
+        let matrix = Matrix4x4::identity()
+            .skew(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            .rotate_y(0.5)
+            .translate(1.0, 2.0, 3.0);
+        let transform = Transform::new(matrix).unwrap();
+
+        assert_approx_4_by_4_eq!(*transform.inverse_matrix(), matrix.inverse().unwrap());
+        assert_approx_4_by_4_eq!(
+            *transform.inverse_transpose(),
+            matrix.inverse().unwrap().transpose()
+        );
+        assert_eq!(Transform::new(Matrix4x4::scaling(1.0, 0.0, 1.0)), None);
+        assert!(Transform::new(Matrix4x4::translation(1.0e12, 0.0, 0.0)).is_some());
+    }
+
+    #[test]
+    fn composing_updates_all_matrices() {
+        let transform = Transform::identity()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+        let matrix = Matrix4x4::identity()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+
+        assert_approx_4_by_4_eq!(*transform.matrix(), matrix);
+        assert_approx_4_by_4_eq!(*transform.inverse_matrix(), matrix.inverse().unwrap());
+        assert_approx_4_by_4_eq!(
+            *transform.inverse_transpose(),
+            matrix.inverse().unwrap().transpose()
+        );
+        assert_eq!(
+            transform.transform_point(Point::new(1.0, 0.0, 1.0)),
+            Point::new(15.0, 0.0, 7.0)
+        );
+    }
+
+    #[test]
+    fn inverting_a_transform() {
+        let transform =
+            Transform::translation(5.0, -3.0, 2.0).rotate(Quaternion::from_euler(0.1, 0.2, 0.3));
+        let p = Point::new(-3.0, 4.0, 5.0);
+
+        assert_eq!(
+            transform
+                .inverse()
+                .transform_point(transform.transform_point(p)),
+            p
+        );
+        assert_eq!(transform.inverse().inverse(), transform);
+    }
+
+    #[test]
+    fn transforming_vectors_and_normals() {
+        let translation = Transform::translation(5.0, -3.0, 2.0);
+        let v = Vector::new(-3.0, 4.0, 5.0);
+        assert_eq!(translation.transform_vector(v), v);
+
+        // a normal on a plane squashed along y tilts towards y
+        let squash = Transform::scaling(1.0, 0.5, 1.0);
+        let n = squash.transform_normal(Vector::new(0.0, 1.0, 1.0).normalize());
+        let tangent = squash.transform_vector(Vector::new(0.0, 1.0, -1.0));
+        assert_approx_eq!(n.dot(&tangent), 0.0);
+        assert!(n.y > n.z);
+    }
+
+    #[test]
+    fn transforming_rays() {
+        let r = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+
+        let moved = Transform::translation(3.0, 4.0, 5.0).transform_ray(r);
+        assert_eq!(moved.origin, Point::new(4.0, 6.0, 8.0));
+        assert_eq!(moved.direction, Vector::new(0.0, 1.0, 0.0));
+
+        let scaled = Transform::scaling(2.0, 3.0, 4.0).transform_ray(r);
+        assert_eq!(scaled.origin, Point::new(2.0, 6.0, 12.0));
+        assert_eq!(scaled.direction, Vector::new(0.0, 3.0, 0.0));
+    }
+}